@@ -0,0 +1,436 @@
+//! module provides caching wrappers around product catalogues and in use factors access, so that a
+//! single cache can be shared across many requests to avoid repeated reads from the PCDB store
+
+use crate::ResolveProductsResult;
+use crate::in_use_factors::{InUseFactorsAccess, InUseFactorsEntry, InUseFactorsInaccessibleError};
+use crate::products::{Product, ProductCatalogue};
+use smartstring::alias::String as SmartString;
+use std::any::Any;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Limits on what a cache holds.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheConfig {
+    /// The maximum number of entries held, after which the least recently used entries are evicted.
+    pub max_entries: usize,
+    /// How long an entry is served from the cache before it is fetched again.
+    pub time_to_live: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            time_to_live: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// A snapshot of how a cache has performed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// The proportion of lookups that were served from the cache, or 0 if there have been none.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+struct CacheEntry<V> {
+    value: V,
+    inserted_at: Instant,
+    last_used_at: Instant,
+}
+
+struct Cache<K, V> {
+    config: CacheConfig,
+    entries: Mutex<HashMap<K, CacheEntry<V>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Cache<K, V> {
+    fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: Default::default(),
+            hits: Default::default(),
+            misses: Default::default(),
+            evictions: Default::default(),
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let value = match entries.get_mut(key) {
+            Some(entry) if now.duration_since(entry.inserted_at) < self.config.time_to_live => {
+                entry.last_used_at = now;
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        match value {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        value
+    }
+
+    fn insert(&self, key: K, value: V) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        if !entries.contains_key(&key) && entries.len() >= self.config.max_entries {
+            let count_before = entries.len();
+            let time_to_live = self.config.time_to_live;
+            entries.retain(|_, entry| now.duration_since(entry.inserted_at) < time_to_live);
+
+            if entries.len() >= self.config.max_entries {
+                let mut by_last_use: Vec<_> = entries
+                    .iter()
+                    .map(|(key, entry)| (entry.last_used_at, key.clone()))
+                    .collect();
+                by_last_use.sort_by_key(|(last_used_at, _)| *last_used_at);
+                let excess = entries.len() + 1 - self.config.max_entries;
+                for (_, key) in by_last_use.into_iter().take(excess) {
+                    entries.remove(&key);
+                }
+            }
+
+            self.evictions
+                .fetch_add((count_before - entries.len()) as u64, Ordering::Relaxed);
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                inserted_at: now,
+                last_used_at: now,
+            },
+        );
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+/// Product catalogue that serves products from a cache where it can, falling back to the wrapped
+/// catalogue for any products that are not cached (or whose cache entries have expired).
+pub struct CachingProductCatalogue<C> {
+    inner: C,
+    cache: Cache<SmartString, Product>,
+}
+
+impl<C: ProductCatalogue> CachingProductCatalogue<C> {
+    pub fn new(inner: C, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            cache: Cache::new(config),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    pub fn clear(&self) {
+        self.cache.clear()
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<C: ProductCatalogue + Sync> ProductCatalogue for CachingProductCatalogue<C> {
+    async fn find_products_for_references(
+        &self,
+        product_references: &[SmartString],
+    ) -> ResolveProductsResult<HashMap<SmartString, Product>> {
+        let mut products = HashMap::with_capacity(product_references.len());
+        let mut uncached_references = vec![];
+
+        for product_reference in product_references {
+            match self.cache.get(product_reference) {
+                Some(product) => {
+                    products.insert(product_reference.clone(), product);
+                }
+                None => uncached_references.push(product_reference.clone()),
+            }
+        }
+
+        if !uncached_references.is_empty() {
+            let fetched = self
+                .inner
+                .find_products_for_references(&uncached_references)
+                .await?;
+            for (product_reference, product) in fetched {
+                self.cache
+                    .insert(product_reference.clone(), product.clone());
+                products.insert(product_reference, product);
+            }
+        }
+
+        Ok(products)
+    }
+}
+
+/// In use factors access that serves each kind of in use factors data from a cache where it can,
+/// falling back to the wrapped access when it is not cached (or its cache entry has expired).
+pub struct CachingInUseFactorsAccess<A> {
+    inner: A,
+    cache: Cache<&'static str, Arc<dyn Any + Send + Sync>>,
+}
+
+impl<A: InUseFactorsAccess> CachingInUseFactorsAccess<A> {
+    pub fn new(inner: A, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            cache: Cache::new(config),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    pub fn clear(&self) {
+        self.cache.clear()
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+}
+
+impl<A: InUseFactorsAccess + Sync> InUseFactorsAccess for CachingInUseFactorsAccess<A> {
+    async fn in_use_factors<T: InUseFactorsEntry>(
+        &self,
+    ) -> Result<Vec<T>, InUseFactorsInaccessibleError> {
+        if let Some(entries) = self
+            .cache
+            .get(&T::entry_id())
+            .and_then(|cached| cached.downcast_ref::<Vec<T>>().cloned())
+        {
+            return Ok(entries);
+        }
+
+        let entries = self.inner.in_use_factors::<T>().await?;
+        self.cache.insert(T::entry_id(), Arc::new(entries.clone()));
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ResolvePcdbProductsError;
+    use crate::in_use_factors::MVInUseFactorEntry;
+    use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
+    use rstest::{fixture, rstest};
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingCatalogue {
+        products: HashMap<SmartString, Product>,
+        requested_references: AtomicUsize,
+    }
+
+    impl ProductCatalogue for CountingCatalogue {
+        async fn find_products_for_references(
+            &self,
+            product_references: &[SmartString],
+        ) -> ResolveProductsResult<HashMap<SmartString, Product>> {
+            self.requested_references
+                .fetch_add(product_references.len(), Ordering::SeqCst);
+            product_references
+                .iter()
+                .map(|reference| {
+                    self.products
+                        .get(reference)
+                        .cloned()
+                        .map(|product| (reference.clone(), product))
+                        .ok_or_else(|| {
+                            ResolvePcdbProductsError::UnknownProductReferences(
+                                reference.to_string().into(),
+                            )
+                        })
+                })
+                .collect()
+        }
+    }
+
+    #[fixture]
+    fn counting_catalogue() -> CountingCatalogue {
+        CountingCatalogue {
+            products: serde_json::from_str(include_str!("transform/fixtures/boilers_pcdb.json"))
+                .unwrap(),
+            requested_references: Default::default(),
+        }
+    }
+
+    fn references(references: &[&str]) -> Vec<SmartString> {
+        references.iter().map(|&r| r.into()).collect()
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_cached_products_are_not_refetched(counting_catalogue: CountingCatalogue) {
+        let catalogue = CachingProductCatalogue::new(counting_catalogue, Default::default());
+
+        catalogue
+            .find_products_for_references(&references(&["boiler"]))
+            .await
+            .unwrap();
+        let products = catalogue
+            .find_products_for_references(&references(&["boiler", "boiler_unknown_location"]))
+            .await
+            .unwrap();
+
+        assert_eq!(products.len(), 2);
+        assert_eq!(
+            catalogue
+                .inner()
+                .requested_references
+                .load(Ordering::SeqCst),
+            2
+        );
+        assert_eq!(
+            catalogue.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                evictions: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_expired_products_are_refetched(counting_catalogue: CountingCatalogue) {
+        let catalogue = CachingProductCatalogue::new(
+            counting_catalogue,
+            CacheConfig {
+                time_to_live: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+
+        for _ in 0..2 {
+            catalogue
+                .find_products_for_references(&references(&["boiler"]))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            catalogue
+                .inner()
+                .requested_references
+                .load(Ordering::SeqCst),
+            2
+        );
+        assert_eq!(catalogue.stats().hits, 0);
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_least_recently_used_products_are_evicted(counting_catalogue: CountingCatalogue) {
+        let catalogue = CachingProductCatalogue::new(
+            counting_catalogue,
+            CacheConfig {
+                max_entries: 1,
+                ..Default::default()
+            },
+        );
+
+        for reference in ["boiler", "boiler_unknown_location", "boiler"] {
+            catalogue
+                .find_products_for_references(&references(&[reference]))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            catalogue
+                .inner()
+                .requested_references
+                .load(Ordering::SeqCst),
+            3
+        );
+        assert_eq!(catalogue.stats().evictions, 2);
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_errors_are_not_cached(counting_catalogue: CountingCatalogue) {
+        let catalogue = CachingProductCatalogue::new(counting_catalogue, Default::default());
+
+        for _ in 0..2 {
+            assert!(
+                catalogue
+                    .find_products_for_references(&references(&["unknown"]))
+                    .await
+                    .is_err()
+            );
+        }
+
+        assert_eq!(catalogue.stats().misses, 2);
+    }
+
+    #[tokio::test]
+    async fn test_in_use_factors_are_cached_per_entry_type() {
+        let access =
+            CachingInUseFactorsAccess::new(FixtureBackedInUseFactorsAccess, Default::default());
+
+        let first = access.in_use_factors::<MVInUseFactorEntry>().await.unwrap();
+        let second = access.in_use_factors::<MVInUseFactorEntry>().await.unwrap();
+
+        assert_eq!(first.len(), second.len());
+        assert_eq!(
+            access.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! module provides detection of values given in the input that resolving product references
+//! would replace with different values from the PCDB, and what to do about them
+//!
+//! Transforms write resolved values through a [`ResolvedEntry`], so conflicts are found and the
+//! policy applied as each value is written rather than by comparing whole documents afterwards.
+
+use crate::ResolveProductsResult;
+use crate::errors::ResolvePcdbProductsError;
+use crate::report::push_pointer_token;
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+
+/// What to do when a value given in the input differs from the value the PCDB gives for the same
+/// field.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConflictPolicy {
+    /// Use the PCDB value.
+    #[default]
+    PcdbWins,
+    /// Keep the value given in the input.
+    UserWins,
+    /// Fail with [`ResolvePcdbProductsError::ConflictingValues`].
+    ErrorOnConflict,
+}
+
+/// A field that was given a value in the input that differs from the value the PCDB gives for it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FieldConflict {
+    /// JSON pointer to the field within the document.
+    pub pointer: String,
+    pub input_value: JsonValue,
+    pub pcdb_value: JsonValue,
+}
+
+impl Display for FieldConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: input value {}, PCDB value {}",
+            self.pointer, self.input_value, self.pcdb_value
+        )
+    }
+}
+
+/// The conflicts found while resolving a document, and the policy applied to each as it was found.
+#[derive(Debug, Default)]
+pub(crate) struct Conflicts {
+    policy: ConflictPolicy,
+    found: Vec<FieldConflict>,
+}
+
+impl Conflicts {
+    pub(crate) fn new(policy: ConflictPolicy) -> Self {
+        Self {
+            policy,
+            found: vec![],
+        }
+    }
+
+    /// Every conflict found, whichever value was kept, or an error if the policy is to error on
+    /// any.
+    pub(crate) fn into_result(self) -> ResolveProductsResult<Vec<FieldConflict>> {
+        if self.policy == ConflictPolicy::ErrorOnConflict && !self.found.is_empty() {
+            return Err(ResolvePcdbProductsError::ConflictingValues(self.found));
+        }
+
+        Ok(self.found)
+    }
+}
+
+/// An entry of the document that resolved values are being written into.
+///
+/// Each value written with [`insert`](Self::insert) is compared with any value the input already
+/// gave for the field, and the resolver's [`ConflictPolicy`] is applied there and then, so that
+/// anything derived from the field afterwards sees the value that was kept.
+pub struct ResolvedEntry<'a> {
+    fields: &'a mut Map<String, JsonValue>,
+    pointer: String,
+    conflicts: &'a mut Conflicts,
+}
+
+impl<'a> ResolvedEntry<'a> {
+    pub(crate) fn new(
+        fields: &'a mut Map<String, JsonValue>,
+        pointer: impl Into<String>,
+        conflicts: &'a mut Conflicts,
+    ) -> Self {
+        Self {
+            fields,
+            pointer: pointer.into(),
+            conflicts,
+        }
+    }
+
+    /// JSON pointer to the entry within the document.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Writes a resolved value into a field of the entry, returning the value the field is left
+    /// with.
+    ///
+    /// Where the input gives a different value for the field, the difference is recorded as a
+    /// conflict, and the input value is kept if the policy is [`ConflictPolicy::UserWins`].
+    pub fn insert(&mut self, key: String, mut value: JsonValue) -> &JsonValue {
+        if let Some(input_value) = self.fields.get(&key) {
+            let mut pointer = self.pointer.clone();
+            push_pointer_token(&mut pointer, &key);
+            let field_length = pointer.len();
+            let mut conflicts = vec![];
+            compare(input_value, &value, &mut pointer, &mut conflicts);
+
+            if self.conflicts.policy == ConflictPolicy::UserWins {
+                for conflict in &conflicts {
+                    if let Some(kept) = value.pointer_mut(&conflict.pointer[field_length..]) {
+                        *kept = conflict.input_value.clone();
+                    }
+                }
+            }
+            self.conflicts.found.extend(conflicts);
+        }
+
+        self.fields.insert(key.clone(), value);
+        &self.fields[&key]
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        self.fields.remove(key)
+    }
+
+    /// The object held in a field of the entry, created if the entry does not have the field, for
+    /// writing resolved values into. Gives `None` if the field holds something other than an object.
+    pub fn nested(&mut self, key: &str) -> Option<ResolvedEntry<'_>> {
+        let mut pointer = self.pointer.clone();
+        push_pointer_token(&mut pointer, key);
+        let fields = self
+            .fields
+            .entry(key)
+            .or_insert_with(|| JsonValue::Object(Map::new()))
+            .as_object_mut()?;
+
+        Some(ResolvedEntry {
+            fields,
+            pointer,
+            conflicts: self.conflicts,
+        })
+    }
+}
+
+impl Deref for ResolvedEntry<'_> {
+    type Target = Map<String, JsonValue>;
+
+    fn deref(&self) -> &Self::Target {
+        self.fields
+    }
+}
+
+fn compare(
+    before: &JsonValue,
+    after: &JsonValue,
+    pointer: &mut String,
+    conflicts: &mut Vec<FieldConflict>,
+) {
+    match (before, after) {
+        (JsonValue::Object(before), JsonValue::Object(after)) => {
+            // fields that only the input gives are not conflicts
+            for (key, before_value) in before {
+                if let Some(after_value) = after.get(key) {
+                    let length = pointer.len();
+                    push_pointer_token(pointer, key);
+                    compare(before_value, after_value, pointer, conflicts);
+                    pointer.truncate(length);
+                }
+            }
+        }
+        (JsonValue::Array(before), JsonValue::Array(after)) if before.len() == after.len() => {
+            for (index, (before_value, after_value)) in before.iter().zip(after).enumerate() {
+                let length = pointer.len();
+                push_pointer_token(pointer, &index.to_string());
+                compare(before_value, after_value, pointer, conflicts);
+                pointer.truncate(length);
+            }
+        }
+        (JsonValue::Number(before_number), JsonValue::Number(after_number))
+            if before_number.as_f64() == after_number.as_f64() => {}
+        (before, after) if before != after => conflicts.push(FieldConflict {
+            pointer: pointer.clone(),
+            input_value: before.clone(),
+            pcdb_value: after.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn boiler() -> JsonValue {
+        json!({"type": "Boiler", "rated_power": 24, "efficiency_full_load": 0.9})
+    }
+
+    #[rstest]
+    #[case(ConflictPolicy::PcdbWins, json!(30.0))]
+    #[case(ConflictPolicy::UserWins, json!(24))]
+    fn test_conflicting_values_are_recorded_and_resolved_by_policy(
+        #[case] policy: ConflictPolicy,
+        #[case] expected_rated_power: JsonValue,
+    ) {
+        let mut boiler = boiler();
+        let mut conflicts = Conflicts::new(policy);
+        let mut entry = ResolvedEntry::new(
+            boiler.as_object_mut().unwrap(),
+            "/HeatSourceWet/boiler",
+            &mut conflicts,
+        );
+
+        assert_eq!(
+            entry.insert("rated_power".into(), json!(30.0)),
+            &expected_rated_power
+        );
+        // equal numbers and fields not in the input are not conflicts
+        entry.insert("efficiency_full_load".into(), json!(0.9));
+        entry.insert("power_max".into(), json!(31.5));
+
+        assert_eq!(
+            conflicts.into_result().unwrap(),
+            [FieldConflict {
+                pointer: "/HeatSourceWet/boiler/rated_power".into(),
+                input_value: json!(24),
+                pcdb_value: json!(30.0),
+            }]
+        );
+        assert_eq!(boiler["rated_power"], expected_rated_power);
+        assert_eq!(boiler["power_max"], json!(31.5));
+    }
+
+    #[test]
+    fn test_conflicting_values_error_when_policy_is_to_error() {
+        let mut boiler = boiler();
+        let mut conflicts = Conflicts::new(ConflictPolicy::ErrorOnConflict);
+        ResolvedEntry::new(boiler.as_object_mut().unwrap(), "", &mut conflicts)
+            .insert("rated_power".into(), json!(30.0));
+
+        let result = conflicts.into_result();
+
+        let Err(ResolvePcdbProductsError::ConflictingValues(conflicts)) = result else {
+            panic!("Expected conflicting values to error, got {result:?}");
+        };
+        assert_eq!(conflicts.len(), 1);
+    }
+
+    #[test]
+    fn test_values_within_nested_objects_are_compared_field_by_field() {
+        let mut heat_pump = json!({"boiler": {"rated_power": 24, "efficiency_full_load": 0.9}});
+        let mut conflicts = Conflicts::new(ConflictPolicy::UserWins);
+        let mut entry = ResolvedEntry::new(
+            heat_pump.as_object_mut().unwrap(),
+            "/HeatSourceWet/hp",
+            &mut conflicts,
+        );
+
+        entry.insert(
+            "boiler".into(),
+            json!({"rated_power": 30, "efficiency_full_load": 0.9, "modulation_load": 0.3}),
+        );
+
+        assert_eq!(
+            conflicts
+                .into_result()
+                .unwrap()
+                .iter()
+                .map(|conflict| conflict.pointer.as_str())
+                .collect::<Vec<_>>(),
+            ["/HeatSourceWet/hp/boiler/rated_power"]
+        );
+        assert_eq!(
+            heat_pump["boiler"],
+            json!({"rated_power": 24, "efficiency_full_load": 0.9, "modulation_load": 0.3})
+        );
+    }
+}
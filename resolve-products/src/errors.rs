@@ -1,3 +1,4 @@
+use crate::conflicts::FieldConflict;
 use crate::in_use_factors::InUseFactorsInaccessibleError;
 use crate::products::FuelType;
 use itertools::Itertools;
@@ -14,6 +15,7 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 #[this_error_from_box]
+#[non_exhaustive]
 pub enum ResolvePcdbProductsError {
     #[error("Request content could not be parsed as JSON")]
     InvalidJson,
@@ -48,15 +50,24 @@ pub enum ResolvePcdbProductsError {
     InvalidProduct(String, &'static str),
     #[error("Error encountered while trying to access PCDB store.")]
     AccessError(#[from] Box<aws_sdk_dynamodb::Error>),
+    #[error(
+        "The PCDB store did not process {0} product lookup(s) after retrying, most likely because requests were throttled."
+    )]
+    AccessThrottled(usize),
+    #[cfg(feature = "sqlite-catalogue")]
+    #[error("Error encountered while trying to access SQLite PCDB store: {0}")]
+    SqliteAccessError(Box<rusqlite::Error>),
     #[error("Error encountered while deserializing PCDB products: {0:?}")]
     DeserializeError(#[source] serde_dynamo::Error),
+    #[error("Error encountered while deserializing PCDB products from JSON: {0:?}")]
+    JsonDeserializeError(#[source] serde_json::Error),
     #[cfg(test)]
     #[error("Error encountered while deserializing test product: {0:?}")]
     BadTestProductError(#[source] serde_json::Error),
     #[error("No energy supply provided for fuel type '{0}' to be mapped to")]
     NoEnergySupplyProvidedForFuelType(FuelType),
     #[error("The standing in use factors data was needed for a calculation, but was inaccessible")]
-    InUseFactorsInaccessibleError(#[from] InUseFactorsInaccessibleError),
+    InUseFactorsInaccessibleError(#[from] Box<InUseFactorsInaccessibleError>),
     #[error(
         "The standing in use factors data did not contain an entry for a valid combination of attributes"
     )]
@@ -67,6 +78,69 @@ pub enum ResolvePcdbProductsError {
         "A heat network was indicated that requires a booster heat pump, but no heat pump was present"
     )]
     BoosterHeatPumpNotPresentError,
+    #[error(
+        "Product references were found at locations where they are not supported, so were left unresolved: {}",
+        .0.join(", ")
+    )]
+    UnconsumedProductReferences(Vec<String>),
+    #[error(
+        "The resolved document was not valid under the HEM target schema - this is a bug in resolve-products. The JSON fragment at path {} was not valid under its schema at path {}, reason '{:?}'.",
+        .0.instance_path,
+        .0.schema_path,
+        .0.kind,
+    )]
+    InvalidResolvedDocument(Box<JsonValidationError>),
+    #[error("A schema given for HEM version {0} could not be compiled: {1}")]
+    InvalidSchema(String, Box<JsonValidationError>),
+    #[error(
+        "{} problem{} found while resolving products: {}",
+        .0.len(),
+        if .0.len() == 1 { " was" } else { "s were" },
+        .0.iter().join("; ")
+    )]
+    ResolutionErrors(Vec<LocatedError>),
+    #[error(
+        "Values given in the input differed from the PCDB for: {}",
+        .0.iter().map(|conflict| &conflict.pointer).join(", ")
+    )]
+    ConflictingValues(Vec<FieldConflict>),
+    #[error(
+        "The resolved document could not be unresolved as values resolved from the PCDB have since changed at: {}",
+        .0.join(", ")
+    )]
+    ResolvedValuesChanged(Vec<String>),
+    #[error("Error encountered while writing the resolved document: {0}")]
+    WriteError(#[source] std::io::Error),
+}
+
+impl ResolvePcdbProductsError {
+    /// Whether the error is caused by the content of one entry in the request (in combination with
+    /// the PCDB data it refers to), rather than by the request as a whole or by the PCDB store being
+    /// unavailable. Only errors like this are collected when collecting all errors.
+    pub fn is_entry_error(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidRequestEncounteredAfterSchemaCheck(_)
+                | Self::InvalidCombination(_)
+                | Self::ProductCategoryMismatches(_)
+                | Self::InvalidProductReferenceJson(_)
+                | Self::UnsupportedProductCategory { .. }
+                | Self::InvalidProduct(..)
+                | Self::NoEnergySupplyProvidedForFuelType(_)
+                | Self::InUseFactorEntryMissingError
+                | Self::SubHeatNetworkNotFoundError(..)
+                | Self::BoosterHeatPumpNotPresentError
+        )
+    }
+}
+
+/// An error caused by one entry in the request, located by a JSON pointer to that entry.
+#[derive(Debug, Error)]
+#[error("{error} (at {pointer})")]
+pub struct LocatedError {
+    pub pointer: String,
+    #[source]
+    pub error: ResolvePcdbProductsError,
 }
 
 #[derive(Clone, Debug)]
@@ -99,6 +173,15 @@ impl<T: Display> SingleOrList<T> {
     }
 }
 
+impl<T: Display + PartialEq> SingleOrList<T> {
+    pub fn contains(&self, value: &T) -> bool {
+        match self {
+            SingleOrList::Single(v) => v == value,
+            SingleOrList::List(v) => v.iter().any(|v| v == value),
+        }
+    }
+}
+
 impl<T: Display> From<T> for SingleOrList<T> {
     fn from(v: T) -> Self {
         SingleOrList::Single(v)
@@ -120,6 +203,18 @@ pub struct JsonValidationError {
     schema_path: String,
 }
 
+impl JsonValidationError {
+    /// JSON pointer to the fragment of the document that was not valid.
+    pub fn instance_path(&self) -> &str {
+        &self.instance_path
+    }
+
+    /// JSON pointer to the part of the schema the fragment was not valid under.
+    pub fn schema_path(&self) -> &str {
+        &self.schema_path
+    }
+}
+
 impl Display for JsonValidationError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
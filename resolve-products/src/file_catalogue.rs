@@ -0,0 +1,291 @@
+//! module provides a product catalogue that is loaded from a local export of the PCDB, so that
+//! products can be resolved without access to DynamoDB
+//!
+//! An export is either a JSON object of product records (keyed by anything, as in the test
+//! fixtures) or NDJSON with one product record per line. In use factors records are stored
+//! alongside the products under their entry IDs, with a `data` field containing a list of items.
+
+use crate::ResolveProductsResult;
+use crate::errors::ResolvePcdbProductsError;
+use crate::in_use_factors::{InUseFactorsAccess, InUseFactorsEntry, InUseFactorsInaccessibleError};
+use crate::products::{Product, ProductCatalogue};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use smartstring::alias::String as SmartString;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+/// The layout of a PCDB export file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// A single JSON object whose values are product records.
+    Json,
+    /// Newline-delimited JSON with one product record per line.
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// Works out the format from a file extension, treating `.ndjson` and `.jsonl` files as NDJSON
+    /// and anything else as JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ndjson" | "jsonl") => Self::Ndjson,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// Product catalogue and in use factors access backed by a PCDB export held in memory.
+#[derive(Debug)]
+pub struct FileBackedCatalogue {
+    records: HashMap<String, JsonValue>,
+}
+
+impl FileBackedCatalogue {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, FileCatalogueError> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+
+        Self::from_reader(BufReader::new(file), ExportFormat::from_path(path))
+    }
+
+    pub fn from_reader(
+        reader: impl Read,
+        format: ExportFormat,
+    ) -> Result<Self, FileCatalogueError> {
+        let records: Vec<JsonValue> = match format {
+            ExportFormat::Json => match serde_json::from_reader(reader)? {
+                JsonValue::Object(records) => records.into_iter().map(|(_, v)| v).collect(),
+                _ => return Err(FileCatalogueError::ExportNotAnObject),
+            },
+            ExportFormat::Ndjson => BufReader::new(reader)
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+                .map(|(index, line)| {
+                    serde_json::from_str(&line?).map_err(|source| {
+                        FileCatalogueError::InvalidNdjsonLine {
+                            line: index + 1,
+                            source,
+                        }
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        Self::from_records(records)
+    }
+
+    fn from_records(records: Vec<JsonValue>) -> Result<Self, FileCatalogueError> {
+        records
+            .into_iter()
+            .map(|record| {
+                let id = record
+                    .get("id")
+                    .and_then(JsonValue::as_str)
+                    .ok_or_else(|| FileCatalogueError::RecordWithoutId(record.clone()))?;
+                Ok((id.to_string(), record))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map(|records| Self { records })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl ProductCatalogue for FileBackedCatalogue {
+    async fn find_products_for_references(
+        &self,
+        product_references: &[SmartString],
+    ) -> ResolveProductsResult<HashMap<SmartString, Product>> {
+        let missing_product_references: Vec<String> = product_references
+            .iter()
+            .filter(|reference| !self.records.contains_key(reference.as_str()))
+            .map(|reference| reference.to_string())
+            .collect();
+        if !missing_product_references.is_empty() {
+            return Err(ResolvePcdbProductsError::UnknownProductReferences(
+                missing_product_references.into(),
+            ));
+        }
+
+        product_references
+            .iter()
+            .map(|reference| {
+                let product = Product::deserialize(&self.records[reference.as_str()])
+                    .map_err(ResolvePcdbProductsError::JsonDeserializeError)?;
+                Ok((reference.clone(), product))
+            })
+            .collect()
+    }
+}
+
+impl InUseFactorsAccess for FileBackedCatalogue {
+    async fn in_use_factors<T: InUseFactorsEntry>(
+        &self,
+    ) -> Result<Vec<T>, InUseFactorsInaccessibleError> {
+        let data = self
+            .records
+            .get(T::entry_id())
+            .and_then(|record| record.get("data"))
+            .ok_or(InUseFactorsInaccessibleError::DataKeyMissingFromInUseFactorsRecord)?;
+
+        Vec::<T>::deserialize(data)
+            .map_err(|_| InUseFactorsInaccessibleError::JsonDeserializeError(data.clone()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FileCatalogueError {
+    #[error("Could not read PCDB export: {0}")]
+    Io(#[from] io::Error),
+    #[error("PCDB export was not valid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Line {line} of PCDB export was not valid JSON: {source}")]
+    InvalidNdjsonLine {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("PCDB export in JSON format was expected to be an object of product records")]
+    ExportNotAnObject,
+    #[error("PCDB export contained a record without a string 'id' field: {0}")]
+    RecordWithoutId(JsonValue),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_use_factors::{HotWaterOnlyInUseFactorEntry, MVInUseFactorEntry};
+    use crate::products::Technology;
+    use rstest::{fixture, rstest};
+    use std::io::Cursor;
+
+    const PCDB_EXPORT: &str = include_str!("../tests/fixtures/pcdb_products.json");
+
+    #[fixture]
+    fn catalogue() -> FileBackedCatalogue {
+        FileBackedCatalogue::from_reader(Cursor::new(PCDB_EXPORT), ExportFormat::Json).unwrap()
+    }
+
+    #[fixture]
+    fn ndjson_export() -> String {
+        let records: serde_json::Map<String, JsonValue> =
+            serde_json::from_str(PCDB_EXPORT).unwrap();
+        records
+            .values()
+            .map(|record| format!("{record}\n"))
+            .collect()
+    }
+
+    #[rstest]
+    #[case("products.ndjson", ExportFormat::Ndjson)]
+    #[case("products.jsonl", ExportFormat::Ndjson)]
+    #[case("products.json", ExportFormat::Json)]
+    #[case("products", ExportFormat::Json)]
+    fn test_export_format_from_path(#[case] path: &str, #[case] expected: ExportFormat) {
+        assert_eq!(ExportFormat::from_path(Path::new(path)), expected);
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_finds_products_in_json_export(catalogue: FileBackedCatalogue) {
+        let products = catalogue
+            .find_products_for_references(&["hp".into(), "boiler".into()])
+            .await
+            .unwrap();
+
+        assert_eq!(products.len(), 2);
+        assert!(matches!(
+            products["hp"].technology,
+            Technology::HeatPump { .. }
+        ));
+        assert!(matches!(
+            products["boiler"].technology,
+            Technology::Boiler { .. }
+        ));
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_loads_ndjson_export(catalogue: FileBackedCatalogue, ndjson_export: String) {
+        let ndjson_catalogue =
+            FileBackedCatalogue::from_reader(Cursor::new(ndjson_export), ExportFormat::Ndjson)
+                .unwrap();
+
+        assert_eq!(ndjson_catalogue.len(), catalogue.len());
+        assert!(
+            ndjson_catalogue
+                .find_products_for_references(&["smart_tank".into()])
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_unknown_product_references_are_all_reported(catalogue: FileBackedCatalogue) {
+        let result = catalogue
+            .find_products_for_references(&["hp".into(), "nope".into(), "nada".into()])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::UnknownProductReferences(ref references)) if references.len() == 2
+        ));
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_badly_formed_product_errors(catalogue: FileBackedCatalogue) {
+        let result = catalogue
+            .find_products_for_references(&["hiu_missing_technology_type".into()])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::JsonDeserializeError(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_provides_in_use_factors(catalogue: FileBackedCatalogue) {
+        assert!(
+            !catalogue
+                .in_use_factors::<MVInUseFactorEntry>()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            !catalogue
+                .in_use_factors::<HotWaterOnlyInUseFactorEntry>()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_invalid_ndjson_line_is_reported() {
+        let result = FileBackedCatalogue::from_reader(
+            Cursor::new("{\"id\": \"a\"}\n\nnot json\n"),
+            ExportFormat::Ndjson,
+        );
+
+        assert!(matches!(
+            result,
+            Err(FileCatalogueError::InvalidNdjsonLine { line: 3, .. })
+        ));
+    }
+}
@@ -2,7 +2,8 @@
 //! with one `data` field containing a JSON list of items
 
 use crate::products::{
-    HeatPumpVesselType, MechanicalVentilationDuctType, MechanicalVentilationInstallationType,
+    DynamoDbCatalogueConfig, HeatPumpVesselType, MechanicalVentilationDuctType,
+    MechanicalVentilationInstallationType,
 };
 use aws_sdk_dynamodb::Client as DynamoDbClient;
 use aws_sdk_dynamodb::config::http::HttpResponse;
@@ -15,10 +16,12 @@ use serde::de::DeserializeOwned;
 use serde_dynamo::from_item;
 use serde_repr::Deserialize_repr;
 use std::collections::HashMap;
+use std::pin::Pin;
 use std::sync::Arc;
+use this_error_from_box::this_error_from_box;
 use thiserror::Error;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HotWaterOnlyInUseFactorEntry {
     pub in_use_factor_mismatch: Decimal,
@@ -50,7 +53,7 @@ impl InUseFactorsEntry for HotWaterOnlyInUseFactorEntry {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MVInUseFactorEntry {
     pub(crate) sfp_in_use_factor: Decimal,
@@ -75,37 +78,79 @@ pub enum MechanicalVentilationSystemType {
     DefaultData = 10,
 }
 
-pub trait InUseFactorsEntry: DeserializeOwned {
+pub trait InUseFactorsEntry: DeserializeOwned + Clone + Send + Sync + 'static {
     fn entry_id() -> &'static str;
 }
 
-pub trait InUseFactorsAccess {
-    async fn in_use_factors<T: InUseFactorsEntry>(
+/// A source of the standing in use factors data that is needed for some product calculations.
+pub trait InUseFactorsAccess: Sync {
+    fn in_use_factors<T: InUseFactorsEntry>(
+        &self,
+    ) -> impl Future<Output = Result<Vec<T>, InUseFactorsInaccessibleError>> + Send;
+}
+
+impl<A: InUseFactorsAccess> InUseFactorsAccess for &A {
+    fn in_use_factors<T: InUseFactorsEntry>(
+        &self,
+    ) -> impl Future<Output = Result<Vec<T>, InUseFactorsInaccessibleError>> + Send {
+        (**self).in_use_factors()
+    }
+}
+
+type InUseFactorsFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<Vec<T>, InUseFactorsInaccessibleError>> + Send + 'a>>;
+
+/// [`InUseFactorsAccess`] to each kind of in use factors, in a form that can be held as a trait
+/// object so that transformers can be.
+pub(crate) trait DynInUseFactorsAccess: Sync {
+    fn hot_water_only_in_use_factors(&self)
+    -> InUseFactorsFuture<'_, HotWaterOnlyInUseFactorEntry>;
+
+    fn mv_in_use_factors(&self) -> InUseFactorsFuture<'_, MVInUseFactorEntry>;
+}
+
+impl<A: InUseFactorsAccess> DynInUseFactorsAccess for A {
+    fn hot_water_only_in_use_factors(
         &self,
-    ) -> Result<Vec<T>, InUseFactorsInaccessibleError>;
+    ) -> InUseFactorsFuture<'_, HotWaterOnlyInUseFactorEntry> {
+        Box::pin(self.in_use_factors())
+    }
+
+    fn mv_in_use_factors(&self) -> InUseFactorsFuture<'_, MVInUseFactorEntry> {
+        Box::pin(self.in_use_factors())
+    }
 }
 
-pub struct DynamoDbBackedInUseFactorsAccess<'a> {
-    dynamo_db_client: &'a DynamoDbClient,
+/// In use factors access backed by a table in DynamoDB (by default, the `products` table).
+pub struct DynamoDbBackedInUseFactorsAccess {
+    dynamo_db_client: DynamoDbClient,
+    config: DynamoDbCatalogueConfig,
 }
 
-impl<'a> DynamoDbBackedInUseFactorsAccess<'a> {
-    pub fn new(dynamo_client: &'a DynamoDbClient) -> Self {
+impl DynamoDbBackedInUseFactorsAccess {
+    pub fn new(dynamo_client: &DynamoDbClient) -> Self {
+        Self::with_config(dynamo_client, Default::default())
+    }
+
+    pub fn with_config(dynamo_client: &DynamoDbClient, config: DynamoDbCatalogueConfig) -> Self {
         Self {
-            dynamo_db_client: dynamo_client,
+            // clients share their underlying connections, so are cheap to clone
+            dynamo_db_client: dynamo_client.clone(),
+            config,
         }
     }
 }
 
-impl InUseFactorsAccess for DynamoDbBackedInUseFactorsAccess<'_> {
+impl InUseFactorsAccess for DynamoDbBackedInUseFactorsAccess {
     async fn in_use_factors<T: InUseFactorsEntry>(
         &self,
     ) -> Result<Vec<T>, InUseFactorsInaccessibleError> {
         let data = self
             .dynamo_db_client
             .get_item()
-            .table_name("products")
-            .key("id", AttributeValue::S(T::entry_id().to_string()))
+            .table_name(&self.config.table_name)
+            .set_key(Some(self.config.key_for(T::entry_id())))
+            .consistent_read(self.config.consistent_read)
             .send()
             .await?
             .item
@@ -121,8 +166,7 @@ impl InUseFactorsAccess for DynamoDbBackedInUseFactorsAccess<'_> {
 
         let mut erroring_attribute_values: Vec<AttributeValue> = vec![];
 
-        Ok(data
-            .into_iter()
+        data.into_iter()
             .map(|item| {
                 from_item::<HashMap<String, AttributeValue>, T>({
                     let map = AttributeValue::as_m(&item).cloned();
@@ -137,20 +181,24 @@ impl InUseFactorsAccess for DynamoDbBackedInUseFactorsAccess<'_> {
                 })
             })
             .collect::<Result<Vec<T>, _>>()
-            .map_err(|_| {
-                InUseFactorsInaccessibleError::DeserializeError(erroring_attribute_values)
-            })?)
+            .map_err(|_| InUseFactorsInaccessibleError::DeserializeError(erroring_attribute_values))
     }
 }
 
 #[derive(Debug, Error)]
+#[this_error_from_box]
 #[error("The expected in use factors data was not available on the PCDB data store.")]
+#[non_exhaustive]
 pub enum InUseFactorsInaccessibleError {
     #[error("Could not make query for in use factors against DynamoDB: {0}")]
-    DynamoDbError(#[from] SdkError<GetItemError, HttpResponse>),
+    DynamoDbError(#[from] Box<SdkError<GetItemError, HttpResponse>>),
     DataKeyMissingFromInUseFactorsRecord,
     InUseFactorsRecordDataFieldNotList(AttributeValue),
     DeserializeError(Vec<AttributeValue>),
+    JsonDeserializeError(serde_json::Value),
+    #[cfg(feature = "sqlite-catalogue")]
+    #[error("Could not query for in use factors against SQLite: {0}")]
+    SqliteError(Box<rusqlite::Error>),
     #[cfg(test)]
     #[error("Could not deserialize item into object as expected")]
     IncorrectFixture(String),
@@ -183,11 +231,9 @@ pub mod mocks {
         ) -> Result<Vec<T>, InUseFactorsInaccessibleError> {
             let in_use_factors_json: serde_json::Value = IN_USE_FACTORS[T::entry_id()].to_owned();
 
-            Ok(
-                serde_json::from_value(in_use_factors_json.clone()).map_err(|_| {
-                    InUseFactorsInaccessibleError::IncorrectFixture(in_use_factors_json.to_string())
-                })?,
-            )
+            serde_json::from_value(in_use_factors_json.clone()).map_err(|_| {
+                InUseFactorsInaccessibleError::IncorrectFixture(in_use_factors_json.to_string())
+            })
         }
     }
 
@@ -0,0 +1,175 @@
+//! module provides an account of the product references in a document and what resolving them would
+//! involve, without resolving them, for use in triaging documents that do not resolve as expected
+
+use crate::products::{Product, ProductCatalogue, find_known_products};
+use crate::report::product_reference_locations;
+use crate::transformer::TransformerRegistry;
+use crate::warnings::Warning;
+use crate::{ResolveProductsResult, extract_product_references};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// The product references in a document, with the products they refer to.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Inspection {
+    /// The HEM version the document was validated for.
+    pub hem_version: String,
+    pub references: Vec<InspectedProductReference>,
+    /// Any non-fatal issues found with the document so far.
+    pub warnings: Vec<Warning>,
+}
+
+/// A product reference in a document, the product it refers to (if it could be found) and the
+/// transformer that would resolve it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct InspectedProductReference {
+    /// JSON pointer to the object within the document that holds the reference.
+    pub pointer: String,
+    /// The field the reference is held in (e.g. `product_reference`).
+    pub field: &'static str,
+    pub product_reference: String,
+    /// The product referred to, or `None` if it is not in the catalogue.
+    pub product: Option<InspectedProduct>,
+    /// The name of the transformer that would resolve the reference, or `None` if none would (e.g.
+    /// because the product is of a category that is not expected where it is referenced).
+    pub transformer: Option<&'static str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct InspectedProduct {
+    pub id: String,
+    pub technology_type: String,
+    pub brand_name: Option<String>,
+    pub model_name: Option<String>,
+    pub model_qualifier: Option<String>,
+}
+
+impl From<&Product> for InspectedProduct {
+    fn from(product: &Product) -> Self {
+        Self {
+            id: product.id().to_string(),
+            technology_type: product.technology_type().to_string(),
+            brand_name: product.brand_name().map(ToString::to_string),
+            model_name: product.model_name().map(ToString::to_string),
+            model_qualifier: product.model_qualifier().map(ToString::to_string),
+        }
+    }
+}
+
+/// Lists the product references in the document alongside the products they refer to and the
+/// transformers that would resolve them.
+pub(crate) async fn inspect_references(
+    json: &JsonValue,
+    product_catalogue: &impl ProductCatalogue,
+    transformers: &TransformerRegistry,
+) -> ResolveProductsResult<Vec<InspectedProductReference>> {
+    let products =
+        find_known_products(&extract_product_references(json)?, product_catalogue).await?;
+    let transformer_infos = transformers.transformers();
+
+    Ok(product_reference_locations(json)
+        .into_iter()
+        .map(|(pointer, field, product_reference)| {
+            let product = products.get(product_reference);
+            let entry = json.pointer(&pointer).and_then(JsonValue::as_object);
+            let transformer = product.zip(entry).and_then(|(product, entry)| {
+                transformer_infos
+                    .iter()
+                    .find(|info| info.applies_to(&pointer, field, entry, product.technology_type()))
+                    .map(|info| info.name)
+            });
+
+            InspectedProductReference {
+                field,
+                product_reference: product_reference.to_string(),
+                product: product.map(InspectedProduct::from),
+                transformer,
+                pointer,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ResolvePcdbProductsError;
+    use serde_json::{from_str, json};
+    use smartstring::alias::String as SmartString;
+    use std::collections::HashMap;
+
+    struct MapCatalogue(HashMap<SmartString, Product>);
+
+    impl ProductCatalogue for MapCatalogue {
+        async fn find_products_for_references(
+            &self,
+            product_references: &[SmartString],
+        ) -> ResolveProductsResult<HashMap<SmartString, Product>> {
+            product_references
+                .iter()
+                .map(|reference| {
+                    self.0
+                        .get(reference)
+                        .cloned()
+                        .map(|product| (reference.clone(), product))
+                        .ok_or_else(|| {
+                            ResolvePcdbProductsError::UnknownProductReferences(
+                                reference.to_string().into(),
+                            )
+                        })
+                })
+                .collect()
+        }
+    }
+
+    fn catalogue() -> MapCatalogue {
+        let heat_pumps: HashMap<SmartString, Product> =
+            from_str(include_str!("transform/fixtures/heat_pump_pcdb.json")).unwrap();
+        let boilers: HashMap<SmartString, Product> =
+            from_str(include_str!("transform/fixtures/boilers_pcdb.json")).unwrap();
+        MapCatalogue(heat_pumps.into_iter().chain(boilers).collect())
+    }
+
+    #[tokio::test]
+    async fn test_references_are_listed_with_products_and_transformers() {
+        let json = json!({
+            "HeatSourceWet": {
+                "hp": {"type": "HeatPump", "product_reference": "hp"},
+                "boiler": {"type": "HeatPump", "product_reference": "boiler"},
+                "missing": {"type": "HeatPump", "product_reference": "not-in-catalogue"},
+                "also missing": {"type": "Boiler", "product_reference": "also-not-in-catalogue"},
+            }
+        });
+        let before = json.clone();
+
+        let mut references = inspect_references(&json, &catalogue(), &Default::default())
+            .await
+            .unwrap();
+        references.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+
+        assert_eq!(
+            references
+                .iter()
+                .map(|reference| (
+                    reference.pointer.as_str(),
+                    reference
+                        .product
+                        .as_ref()
+                        .map(|product| product.technology_type.as_str()),
+                    reference.transformer,
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("/HeatSourceWet/also missing", None, None),
+                ("/HeatSourceWet/boiler", Some("CombiBoiler"), None),
+                (
+                    "/HeatSourceWet/hp",
+                    Some("AirSourceHeatPump"),
+                    Some("heat_source_wet")
+                ),
+                ("/HeatSourceWet/missing", None, None),
+            ]
+        );
+        assert_eq!(json, before);
+    }
+}
@@ -1,51 +1,127 @@
+pub mod caching;
+pub mod conflicts;
 pub mod errors;
-pub(crate) mod in_use_factors;
+#[cfg(feature = "file-catalogue")]
+pub mod file_catalogue;
+pub mod in_use_factors;
+pub mod inspection;
+mod output;
 mod products;
+pub mod report;
+mod resolver;
+#[cfg(feature = "sqlite-catalogue")]
+pub mod sqlite_catalogue;
 mod transform;
+pub mod transformer;
+mod unresolve;
+pub mod versions;
+pub mod warnings;
 
-use crate::errors::{JsonPathError, JsonValidationError, ResolvePcdbProductsError};
-pub use crate::products::FuelType;
-use crate::transform::{ResolveProductsResult, transform_json};
+use crate::errors::{JsonPathError, ResolvePcdbProductsError};
+use crate::in_use_factors::{DynamoDbBackedInUseFactorsAccess, InUseFactorsAccess};
+pub use crate::products::{
+    DynamoDbBackedProductCatalogue, DynamoDbCatalogueConfig, FuelType, Product, ProductCatalogue,
+};
+pub use crate::resolver::{Resolution, Resolver, ResolverOptions};
+pub use crate::transform::ResolveProductsResult;
 use aws_sdk_dynamodb::Client as DynamoDbClient;
 use itertools::Itertools;
 use jsonpath_rust::JsonPath;
 use serde_json::Value as JsonValue;
 use smartstring::alias::String;
 use std::fmt::Debug;
-use std::io::{BufReader, Cursor, Read};
+use std::io::{Cursor, Read};
 use std::sync::LazyLock;
 
 pub async fn resolve_products(
     json: impl Read,
     dynamo_client: &DynamoDbClient,
 ) -> ResolveProductsResult<impl Read + Debug> {
-    let reader = BufReader::new(json);
+    resolve_products_with_dynamo_db_config(json, dynamo_client, Default::default()).await
+}
 
-    let mut input: JsonValue =
-        serde_json::from_reader(reader).map_err(|_| ResolvePcdbProductsError::InvalidJson)?;
+/// Resolves product references in the given JSON against DynamoDB, reading from the table and key
+/// attribute set in the given configuration.
+pub async fn resolve_products_with_dynamo_db_config(
+    json: impl Read,
+    dynamo_client: &DynamoDbClient,
+    config: DynamoDbCatalogueConfig,
+) -> ResolveProductsResult<impl Read + Debug> {
+    resolve_products_with_catalogue(
+        json,
+        &DynamoDbBackedProductCatalogue::with_config(dynamo_client, config.clone()),
+        &DynamoDbBackedInUseFactorsAccess::with_config(dynamo_client, config),
+    )
+    .await
+}
 
-    let schema_validator = {
-        let schema = serde_json::from_str(include_str!("./combined_schema.json"))
-            .expect("Schema file was not parseable.");
-        jsonschema::async_validator_for(&schema).await.expect(
-            "Failed to create validator for schema. \
-             This is a bug in resolve-products. Please report it.",
-        )
-    };
+/// Resolves product references in the given JSON against the provided product catalogue and
+/// in use factors data, rather than against DynamoDB.
+///
+/// When resolving many documents, hold on to a [`Resolver`] instead.
+pub async fn resolve_products_with_catalogue<R, C, A>(
+    json: R,
+    product_catalogue: &C,
+    in_use_factors_access: &A,
+) -> ResolveProductsResult<impl Read + Debug + use<R, C, A>>
+where
+    R: Read,
+    C: ProductCatalogue,
+    A: InUseFactorsAccess,
+{
+    let resolved = Resolver::new(product_catalogue, in_use_factors_access)
+        .await
+        .resolve_with_details(json)
+        .await?;
 
-    // validate first
-    if let Err(e) = schema_validator.validate(&input) {
-        return Err(JsonValidationError::from(e).into());
-    }
+    Ok(Cursor::new(resolved.document.to_string()))
+}
+
+/// Resolves product references in the given document against DynamoDB, for callers that already
+/// hold the document as a [`serde_json::Value`].
+pub async fn resolve_products_value(
+    json: JsonValue,
+    dynamo_client: &DynamoDbClient,
+) -> ResolveProductsResult<JsonValue> {
+    resolve_products_value_with_dynamo_db_config(json, dynamo_client, Default::default()).await
+}
 
-    transform_json(&mut input, dynamo_client).await?;
+/// Resolves product references in the given document against DynamoDB, reading from the table and
+/// key attribute set in the given configuration, for callers that already hold the document as a
+/// [`serde_json::Value`].
+pub async fn resolve_products_value_with_dynamo_db_config(
+    json: JsonValue,
+    dynamo_client: &DynamoDbClient,
+    config: DynamoDbCatalogueConfig,
+) -> ResolveProductsResult<JsonValue> {
+    resolve_products_value_with_catalogue(
+        json,
+        &DynamoDbBackedProductCatalogue::with_config(dynamo_client, config.clone()),
+        &DynamoDbBackedInUseFactorsAccess::with_config(dynamo_client, config),
+    )
+    .await
+}
 
-    Ok(Cursor::new(input.to_string()))
+/// Resolves product references in the given document against the provided product catalogue and in
+/// use factors data, for callers that already hold the document as a [`serde_json::Value`].
+pub async fn resolve_products_value_with_catalogue(
+    json: JsonValue,
+    product_catalogue: &impl ProductCatalogue,
+    in_use_factors_access: &impl InUseFactorsAccess,
+) -> ResolveProductsResult<JsonValue> {
+    Resolver::new(product_catalogue, in_use_factors_access)
+        .await
+        .resolve_value(json)
+        .await
 }
 
 pub const PRODUCT_REFERENCE_FIELD: &str = "product_reference";
 
-const ALL_PRODUCT_REFERENCE_FIELDS: [&str; 2] = [PRODUCT_REFERENCE_FIELD, "heat_network_reference"];
+const ALL_PRODUCT_REFERENCE_FIELDS: [&str; 3] = [
+    PRODUCT_REFERENCE_FIELD,
+    "heat_network_reference",
+    "inverter_product_reference",
+];
 
 static PRODUCT_REFERENCE_FIELDS_JSON_PATH_QUERY: LazyLock<std::string::String> =
     LazyLock::new(|| {
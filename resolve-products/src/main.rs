@@ -1,6 +1,12 @@
 use aws_config::BehaviorVersion;
-use clap::Parser;
-use serde_json::Value;
+use clap::{Parser, ValueEnum};
+use resolve_products::conflicts::ConflictPolicy;
+use resolve_products::in_use_factors::{DynamoDbBackedInUseFactorsAccess, InUseFactorsAccess};
+use resolve_products::inspection::Inspection;
+use resolve_products::{
+    DynamoDbBackedProductCatalogue, DynamoDbCatalogueConfig, ProductCatalogue, Resolution,
+    Resolver, ResolverOptions,
+};
 use std::fs::File;
 use std::io::{self, Read};
 use std::path::PathBuf;
@@ -16,6 +22,61 @@ struct Cli {
     /// Pretty-print output JSON
     #[arg(long)]
     pretty: bool,
+
+    /// Name of the DynamoDB table to read products from
+    #[arg(long, default_value = "products")]
+    table_name: String,
+
+    /// Name of the partition key attribute that records are stored under in the DynamoDB table
+    #[arg(long, default_value = "id")]
+    key_attribute: String,
+
+    /// Use strongly consistent reads from DynamoDB
+    #[arg(long)]
+    consistent_read: bool,
+
+    /// Write a report of where resolved values came from (as JSON) to the given path
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Report all problems with the input rather than stopping at the first
+    #[arg(long)]
+    all_errors: bool,
+
+    /// Check the resolved document against the HEM target schema before writing it out
+    #[arg(long)]
+    validate_output: bool,
+
+    /// What to do when a value given in the input differs from the PCDB
+    #[arg(long, value_enum, default_value_t = ConflictPolicyArg::PcdbWins)]
+    conflict_policy: ConflictPolicyArg,
+
+    /// List the product references in the input, with the products they refer to and the
+    /// transforms that would apply, instead of resolving them
+    #[arg(long)]
+    inspect: bool,
+
+    /// Resolve against a local PCDB export (JSON or NDJSON) instead of DynamoDB
+    #[cfg(feature = "file-catalogue")]
+    #[arg(long)]
+    catalogue: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ConflictPolicyArg {
+    PcdbWins,
+    UserWins,
+    ErrorOnConflict,
+}
+
+impl From<ConflictPolicyArg> for ConflictPolicy {
+    fn from(arg: ConflictPolicyArg) -> Self {
+        match arg {
+            ConflictPolicyArg::PcdbWins => ConflictPolicy::PcdbWins,
+            ConflictPolicyArg::UserWins => ConflictPolicy::UserWins,
+            ConflictPolicyArg::ErrorOnConflict => ConflictPolicy::ErrorOnConflict,
+        }
+    }
 }
 
 fn read_input(path: &PathBuf) -> io::Result<String> {
@@ -31,27 +92,133 @@ fn read_input(path: &PathBuf) -> io::Result<String> {
     }
 }
 
-#[::tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+enum Output {
+    Resolved(Resolution),
+    Inspected(Inspection),
+}
+
+async fn resolve_with<C: ProductCatalogue, A: InUseFactorsAccess>(
+    cli: &Cli,
+    input: &[u8],
+    product_catalogue: C,
+    in_use_factors_access: A,
+) -> Result<Output, Box<dyn std::error::Error>> {
+    let resolver = Resolver::new(product_catalogue, in_use_factors_access)
+        .await
+        .with_options(ResolverOptions {
+            report: cli.report.is_some(),
+            collect_all_errors: cli.all_errors,
+            validate_output: cli.validate_output,
+            conflict_policy: cli.conflict_policy.into(),
+        });
+
+    Ok(if cli.inspect {
+        Output::Inspected(resolver.inspect(input).await?)
+    } else {
+        Output::Resolved(resolver.resolve_with_details(input).await?)
+    })
+}
+
+fn catalogue_config(cli: &Cli) -> DynamoDbCatalogueConfig {
+    DynamoDbCatalogueConfig {
+        table_name: cli.table_name.clone(),
+        key_attribute: cli.key_attribute.clone(),
+        consistent_read: cli.consistent_read,
+        ..Default::default()
+    }
+}
+
+async fn resolve(cli: &Cli, input: &[u8]) -> Result<Output, Box<dyn std::error::Error>> {
+    #[cfg(feature = "file-catalogue")]
+    if let Some(catalogue_path) = &cli.catalogue {
+        let catalogue =
+            resolve_products::file_catalogue::FileBackedCatalogue::from_path(catalogue_path)?;
+        return resolve_with(cli, input, &catalogue, &catalogue).await;
+    }
+
     let config = aws_config::defaults(BehaviorVersion::latest())
         .endpoint_url("http://localhost:8000")
         .load()
         .await;
     let dynamo_client = aws_sdk_dynamodb::Client::new(&config);
+    let catalogue_config = catalogue_config(cli);
 
+    resolve_with(
+        cli,
+        input,
+        DynamoDbBackedProductCatalogue::with_config(&dynamo_client, catalogue_config.clone()),
+        DynamoDbBackedInUseFactorsAccess::with_config(&dynamo_client, catalogue_config),
+    )
+    .await
+}
+
+#[::tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     let input_str = read_input(&cli.input)?;
     let input_bytes = input_str.as_bytes();
 
-    let mut resolved = resolve_products::resolve_products(input_bytes, &dynamo_client).await?;
+    let resolution = match resolve(&cli, input_bytes).await? {
+        Output::Resolved(resolution) => resolution,
+        Output::Inspected(inspection) => {
+            if cli.pretty {
+                serde_json::to_writer_pretty(io::stdout(), &inspection)?;
+            } else {
+                serde_json::to_writer(io::stdout(), &inspection)?;
+            }
+            return Ok(());
+        }
+    };
+
+    if let (Some(report_path), Some(report)) = (&cli.report, &resolution.report) {
+        serde_json::to_writer_pretty(File::create(report_path)?, report)?;
+    }
+
+    for warning in &resolution.warnings {
+        eprintln!("warning: {warning}");
+    }
+
+    for conflict in &resolution.conflicts {
+        eprintln!("conflict: {conflict}");
+    }
 
     if cli.pretty {
-        let pretty: Value = serde_json::from_reader(resolved).unwrap();
-        serde_json::to_writer_pretty(io::stdout(), &pretty).unwrap();
+        serde_json::to_writer_pretty(io::stdout(), &resolution.document)?;
     } else {
-        io::copy(&mut resolved, &mut io::stdout())?;
+        serde_json::to_writer(io::stdout(), &resolution.document)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogue_config_defaults() {
+        let config = catalogue_config(&Cli::parse_from(["resolve-products", "input.json"]));
+
+        assert_eq!(config.table_name, "products");
+        assert_eq!(config.key_attribute, "id");
+        assert!(!config.consistent_read);
+    }
+
+    #[test]
+    fn test_catalogue_config_from_flags() {
+        let config = catalogue_config(&Cli::parse_from([
+            "resolve-products",
+            "--table-name",
+            "pcdb",
+            "--key-attribute",
+            "pk",
+            "--consistent-read",
+            "input.json",
+        ]));
+
+        assert_eq!(config.table_name, "pcdb");
+        assert_eq!(config.key_attribute, "pk");
+        assert!(config.consistent_read);
+    }
+}
@@ -0,0 +1,91 @@
+//! module provides writing of resolved documents to asynchronous writers, serializing them in chunks
+//! as they are written rather than into one string up front
+
+use serde_json::Value as JsonValue;
+use std::io::{self, Write};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+/// The size of the chunks that documents are serialized in before being written.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// How many serialized chunks can wait to be written before serialization pauses.
+const CHUNKS_IN_FLIGHT: usize = 4;
+
+/// Writer that gathers what is written to it into chunks, and sends each one on when it is full.
+struct ChunkSender {
+    chunk: Vec<u8>,
+    sender: mpsc::Sender<Vec<u8>>,
+}
+
+impl Write for ChunkSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.chunk.extend_from_slice(buf);
+        if self.chunk.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.chunk.is_empty() {
+            let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(CHUNK_SIZE));
+            self.sender
+                .blocking_send(chunk)
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes the document as JSON into the given writer.
+///
+/// Serialization happens on a blocking thread, handing chunks over to be written as they fill, so
+/// that at most a few chunks of the serialized document are held at once.
+pub(crate) async fn write_json_async(
+    document: JsonValue,
+    mut writer: impl AsyncWrite + Unpin,
+) -> io::Result<()> {
+    let (sender, mut receiver) = mpsc::channel(CHUNKS_IN_FLIGHT);
+    let serialization = tokio::task::spawn_blocking(move || {
+        let mut chunk_sender = ChunkSender {
+            chunk: Vec::with_capacity(CHUNK_SIZE),
+            sender,
+        };
+        serde_json::to_writer(&mut chunk_sender, &document)?;
+        chunk_sender.flush()
+    });
+
+    while let Some(chunk) = receiver.recv().await {
+        writer.write_all(&chunk).await?;
+    }
+    serialization.await.map_err(io::Error::other)??;
+
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_document_larger_than_a_chunk_is_written_whole() {
+        let document = json!({
+            "values": (0..CHUNK_SIZE).map(|i| format!("value {i}")).collect::<Vec<_>>()
+        });
+        let mut written = vec![];
+
+        write_json_async(document.clone(), &mut written)
+            .await
+            .unwrap();
+
+        assert!(written.len() > CHUNK_SIZE * CHUNKS_IN_FLIGHT);
+        assert_eq!(
+            serde_json::from_slice::<JsonValue>(&written).unwrap(),
+            document
+        );
+    }
+}
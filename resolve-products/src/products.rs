@@ -14,6 +14,9 @@ use serde_repr::Deserialize_repr;
 use serde_valid::Validate;
 use smartstring::alias::String;
 use std::collections::{HashMap, HashSet};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub(crate) async fn find_products_for_references(
     product_references: &[String],
@@ -37,18 +40,95 @@ pub(crate) async fn find_product_for_reference(
         .expect("Fetched product reference expected to be present in result"))
 }
 
-#[derive(Debug, Deserialize, Validate)]
+/// A product record from the PCDB.
+///
+/// Products can be deserialized from the same record shape as is held in the PCDB store, so that
+/// alternative [`ProductCatalogue`] implementations can build them from their own storage.
+#[derive(Clone, Debug, Deserialize, Validate)]
+#[serde(try_from = "Value")]
+pub struct Product {
+    id: String,
+    brand_name: Option<String>,
+    model_name: Option<String>,
+    model_qualifier: Option<String>,
+    technology_type: String,
+    pub(crate) technology: Technology,
+    record: Arc<Value>,
+}
+
+impl Product {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn brand_name(&self) -> Option<&str> {
+        self.brand_name.as_deref()
+    }
+
+    pub fn model_name(&self) -> Option<&str> {
+        self.model_name.as_deref()
+    }
+
+    pub fn model_qualifier(&self) -> Option<&str> {
+        self.model_qualifier.as_deref()
+    }
+
+    /// The technology type as given in the PCDB record (e.g. "AirSourceHeatPump"), which can be
+    /// more specific than the category of product it is resolved as.
+    pub fn technology_type(&self) -> &str {
+        &self.technology_type
+    }
+
+    /// The PCDB record as it was read, so that a custom
+    /// [`EntryTransformer`](crate::transformer::EntryTransformer) can read its fields.
+    pub fn record(&self) -> &Value {
+        &self.record
+    }
+}
+
+/// The shape of a product record, which is read through a JSON value so that the technology type
+/// is kept as it appears in the record.
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct Product {
+struct ProductRecord {
     id: String,
     brand_name: Option<String>,
     model_name: Option<String>,
     model_qualifier: Option<String>,
     #[serde(flatten)]
-    pub(crate) technology: Technology,
+    technology: Technology,
 }
 
-#[derive(Debug, Deserialize)]
+impl TryFrom<Value> for Product {
+    type Error = serde_json::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let ProductRecord {
+            id,
+            brand_name,
+            model_name,
+            model_qualifier,
+            technology,
+        } = ProductRecord::deserialize(&value)?;
+        let technology_type = value
+            .get("technologyType")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .into();
+
+        Ok(Self {
+            id,
+            brand_name,
+            model_name,
+            model_qualifier,
+            technology_type,
+            technology,
+            record: Arc::new(value),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
 #[serde(
     tag = "technologyType",
     rename_all = "camelCase",
@@ -248,6 +328,44 @@ pub(crate) enum Technology {
         /// Heat exchanger surface area (unit: m²)
         heat_exchanger_surface_area: Option<Decimal>,
     },
+    #[serde(alias = "SplitAirConditioning", alias = "PackagedAirConditioning")]
+    AirConditioning {
+        fuel: FuelType,
+        /// Rated cooling capacity (unit: kW)
+        cooling_capacity: Decimal,
+        /// Energy efficiency ratio at rated conditions (unitless)
+        eer: Decimal,
+        /// Seasonal energy efficiency ratio (unitless)
+        seer: Decimal,
+        /// Convective fraction of the cooling output (unitless)
+        frac_convective: Decimal,
+    },
+    #[serde(rename = "SolarThermalCollector")]
+    SolarThermalCollector {
+        /// Aperture area of a single collector module (unit: m²)
+        aperture_area: Decimal,
+        /// Zero-loss collector efficiency (unitless)
+        zero_loss_efficiency: Decimal,
+        /// First order heat loss coefficient, a1 (unit: W/(m².K))
+        first_order_heat_loss_coefficient: Decimal,
+        /// Second order heat loss coefficient, a2 (unit: W/(m².K²))
+        second_order_heat_loss_coefficient: Decimal,
+        /// Hemispherical incidence angle modifier (unitless)
+        incidence_angle_modifier: Decimal,
+        /// Power of the collector loop pump (unit: kW)
+        collector_loop_pump_power: Decimal,
+        /// Power of the collector loop pump controls (unit: kW)
+        collector_loop_pump_control_power: Decimal,
+    },
+    #[serde(rename = "HotWaterCylinder")]
+    StorageTank {
+        /// Declared volume of the cylinder (unit: litre)
+        volume_declared: Decimal,
+        /// Declared standby losses due to cylinder insulation at standardised conditions (unit: kWh/24h)
+        daily_losses_declared: Decimal,
+        /// Declared heat exchanger surface area, blank (None) if the cylinder has no heat exchanger (unit: m²)
+        heat_exchanger_surface_area_declared: Option<Decimal>,
+    },
     #[serde(rename = "HotWaterOnlyHeatPump")]
     HeatPumpHotWaterOnly {
         fuel: FuelType,
@@ -281,6 +399,31 @@ pub(crate) enum Technology {
         #[serde(rename = "flowrate")]
         flow_rate: Decimal,
     },
+    #[serde(rename = "PhotovoltaicModule")]
+    PhotovoltaicModule {
+        /// Peak power of a single module at standard test conditions (unit: kW)
+        peak_power: Decimal,
+    },
+    #[serde(rename = "PhotovoltaicInverter")]
+    PhotovoltaicInverter {
+        /// Rated AC power output (unit: kW)
+        #[serde(rename = "ratedPowerAC")]
+        rated_power_ac: Decimal,
+        inverter_type: PhotovoltaicInverterType,
+    },
+    #[serde(rename = "ElectricBattery")]
+    ElectricBattery {
+        /// Usable capacity of the battery (unit: kWh)
+        capacity: Decimal,
+        /// Charge/discharge round trip efficiency of the battery system (unitless)
+        charge_discharge_efficiency_round_trip: Decimal,
+        /// Minimum one way trip charge rate (unit: kW)
+        minimum_charge_rate: Decimal,
+        /// Maximum one way trip charge rate (unit: kW)
+        maximum_charge_rate: Decimal,
+        /// Maximum one way trip discharge rate (unit: kW)
+        maximum_discharge_rate: Decimal,
+    },
     // capture any unknown product type that is referenced so we are able to construe it as an
     // invalid choice for the payload in question rather than a badly-formed product in the HEM database
     #[serde(other)]
@@ -334,7 +477,7 @@ pub(crate) struct HeatPumpExhaustAirMixedFields {
     pub(crate) eahp_mixed_max_temp: Decimal,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct HeatPumpTestDatum {
     #[serde(rename = "design_flow_temp")]
     pub(crate) design_flow_temperature: i32,
@@ -397,7 +540,7 @@ pub(crate) enum BoilerLocation {
     Unknown,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct HeatBatteryDryCoreTestDatum {
     /// Charge level (e.g., percentage or step index)
@@ -408,7 +551,14 @@ pub(crate) struct HeatBatteryDryCoreTestDatum {
     pub(crate) dry_core_max_output: Decimal,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize_enum_str, PartialEq, Serialize_enum_str)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PhotovoltaicInverterType {
+    StringInverter,
+    OptimisedInverter,
+}
+
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct WwhrsTestDatum {
     pub(crate) flow_rate: Decimal,
@@ -431,7 +581,7 @@ pub(crate) enum StorageHeaterAirFlowType {
     FanAssisted,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ElectricStorageHeaterTestDatum {
     /// Test point number (0 to 100 (TODO: ??? maybe to 1) during heat discharge test)
@@ -442,7 +592,7 @@ pub(crate) struct ElectricStorageHeaterTestDatum {
     pub(crate) dry_core_max_output: Decimal,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct FanCoilTestDatum {
     /// fan speeds (n) for which data are provided in the record
@@ -467,7 +617,7 @@ pub(crate) struct CentralisedMevTestDatum {
     pub(crate) duct_size: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CentralisedMvhrTestDatum {
     /// Whether tested using flexible, rigid ducting or semi-rigid, coded as 1,2 and 3 respectively. Semi-rigid have the same in use factors as rigid.
@@ -480,7 +630,7 @@ pub(crate) struct CentralisedMvhrTestDatum {
     pub(crate) mvhr_eff: Decimal,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CentralisedMvTestDatum {
     /// Whether tested using flexible, rigid ducting or semi-rigid, coded as 1,2 and 3 respectively. Semi-rigid have the same in use factors as rigid.
@@ -499,7 +649,7 @@ pub(crate) enum MechanicalVentilationDuctType {
     SemiRigid = 3,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct DecentralisedMevTestDatum {
     pub(crate) configuration: DecentralisedMevInstallationConfiguration,
@@ -524,7 +674,7 @@ pub(crate) enum DecentralisedMevInstallationConfiguration {
     ThroughWall = 3,
 }
 
-#[derive(Debug, Deserialize_repr, PartialEq)]
+#[derive(Clone, Debug, Deserialize_repr, PartialEq)]
 #[repr(u8)]
 pub(crate) enum MechanicalVentilationInstallationType {
     NotInstalledUnderApprovedScheme = 1,
@@ -547,7 +697,7 @@ pub(crate) enum TappingProfile {
     M,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct HeatPumpHotWaterOnlyTestDatum {
     pub(crate) tapping_profile: TappingProfile,
@@ -561,7 +711,7 @@ pub(crate) struct HeatPumpHotWaterOnlyTestDatum {
     pub(crate) power_standby: Decimal,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct SubHeatNetwork {
     #[serde(rename = "subheatNetworkName")]
     pub(crate) name: String,
@@ -573,68 +723,264 @@ pub(crate) struct SubHeatNetwork {
     pub(crate) primary_energy_factor: Decimal,
 }
 
-pub(crate) trait ProductCatalogue {
-    async fn find_products_for_references(
+/// A source of PCDB products that product references within a request can be resolved against.
+///
+/// Implementations are expected to return a product for every reference asked for, keyed by that
+/// reference, and to fail with [`ResolvePcdbProductsError::UnknownProductReferences`] listing any
+/// references that could not be found.
+pub trait ProductCatalogue: Sync {
+    fn find_products_for_references(
         &self,
         product_references: &[String],
-    ) -> ResolveProductsResult<HashMap<String, Product>>;
+    ) -> impl Future<Output = ResolveProductsResult<HashMap<String, Product>>> + Send;
 }
 
-pub(crate) struct DynamoDbBackedProductCatalogue<'a> {
-    dynamo_db_client: &'a DynamoDbClient,
+impl<C: ProductCatalogue + ?Sized> ProductCatalogue for &C {
+    fn find_products_for_references(
+        &self,
+        product_references: &[String],
+    ) -> impl Future<Output = ResolveProductsResult<HashMap<String, Product>>> + Send {
+        (**self).find_products_for_references(product_references)
+    }
 }
 
-impl<'a> DynamoDbBackedProductCatalogue<'a> {
-    pub(crate) fn new(dynamo_db_client: &'a DynamoDbClient) -> Self {
-        Self { dynamo_db_client }
+type ProductsFuture<'a> =
+    Pin<Box<dyn Future<Output = ResolveProductsResult<HashMap<String, Product>>> + Send + 'a>>;
+
+/// A [`ProductCatalogue`] that can be held as a trait object, so that transformers can be.
+pub(crate) trait DynProductCatalogue: Sync {
+    fn find_products_for_references_boxed<'a>(
+        &'a self,
+        product_references: &'a [String],
+    ) -> ProductsFuture<'a>;
+}
+
+impl<C: ProductCatalogue> DynProductCatalogue for C {
+    fn find_products_for_references_boxed<'a>(
+        &'a self,
+        product_references: &'a [String],
+    ) -> ProductsFuture<'a> {
+        Box::pin(self.find_products_for_references(product_references))
     }
 }
 
-impl ProductCatalogue for DynamoDbBackedProductCatalogue<'_> {
+impl ProductCatalogue for dyn DynProductCatalogue + '_ {
     async fn find_products_for_references(
         &self,
         product_references: &[String],
     ) -> ResolveProductsResult<HashMap<String, Product>> {
-        if product_references.is_empty() {
-            return Ok(HashMap::new());
+        self.find_products_for_references_boxed(product_references)
+            .await
+    }
+}
+
+/// Finds the products for the given references, leaving out any that are not in the catalogue
+/// rather than failing.
+pub(crate) async fn find_known_products(
+    product_references: &[String],
+    product_catalogue: &impl ProductCatalogue,
+) -> ResolveProductsResult<HashMap<String, Product>> {
+    let mut known_references = product_references.to_vec();
+    // catalogues may report only some of the unknown references at a time, so keep asking until
+    // only known references remain
+    loop {
+        if known_references.is_empty() {
+            return Ok(Default::default());
         }
 
-        let keys: Vec<HashMap<::std::string::String, AttributeValue>> = product_references
-            .iter()
-            .map(|product_ref| {
-                HashMap::from([("id".to_string(), AttributeValue::S(product_ref.to_string()))])
-            })
-            .collect();
+        match product_catalogue
+            .find_products_for_references(&known_references)
+            .await
+        {
+            Err(ResolvePcdbProductsError::UnknownProductReferences(unknown)) => {
+                let before = known_references.len();
+                known_references.retain(|reference| !unknown.contains(&reference.to_string()));
+                if known_references.len() == before {
+                    return Err(ResolvePcdbProductsError::UnknownProductReferences(unknown));
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Where and how PCDB records are read from DynamoDB.
+#[derive(Clone, Debug)]
+pub struct DynamoDbCatalogueConfig {
+    /// The name of the table holding product and in use factors records.
+    pub table_name: ::std::string::String,
+    /// The name of the (string) partition key attribute that records are stored under.
+    pub key_attribute: ::std::string::String,
+    /// Whether reads should be strongly consistent rather than eventually consistent.
+    pub consistent_read: bool,
+    /// How many times keys that DynamoDB leaves unprocessed (e.g. when throttling) are requested
+    /// again before giving up.
+    pub max_retries: u32,
+    /// The delay before the first retry of unprocessed keys, which doubles with each further retry.
+    pub retry_base_delay: Duration,
+}
 
-        let results = self
+impl Default for DynamoDbCatalogueConfig {
+    fn default() -> Self {
+        Self {
+            table_name: "products".into(),
+            key_attribute: "id".into(),
+            consistent_read: false,
+            max_retries: 8,
+            retry_base_delay: Duration::from_millis(25),
+        }
+    }
+}
+
+impl DynamoDbCatalogueConfig {
+    pub(crate) fn key_for(&self, id: &str) -> HashMap<::std::string::String, AttributeValue> {
+        HashMap::from([(
+            self.key_attribute.clone(),
+            AttributeValue::S(id.to_string()),
+        )])
+    }
+}
+
+/// Product catalogue backed by a table in DynamoDB (by default, the `products` table).
+pub struct DynamoDbBackedProductCatalogue {
+    dynamo_db_client: DynamoDbClient,
+    config: DynamoDbCatalogueConfig,
+}
+
+impl DynamoDbBackedProductCatalogue {
+    pub fn new(dynamo_db_client: &DynamoDbClient) -> Self {
+        Self::with_config(dynamo_db_client, Default::default())
+    }
+
+    pub fn with_config(dynamo_db_client: &DynamoDbClient, config: DynamoDbCatalogueConfig) -> Self {
+        Self {
+            // clients share their underlying connections, so are cheap to clone
+            dynamo_db_client: dynamo_db_client.clone(),
+            config,
+        }
+    }
+}
+
+type DynamoDbItem = HashMap<::std::string::String, AttributeValue>;
+
+/// The most keys that DynamoDB accepts in a single BatchGetItem request.
+const MAX_KEYS_PER_BATCH_GET: usize = 100;
+
+/// What came back from a single BatchGetItem request: the items found, and any keys that DynamoDB
+/// left unprocessed (typically because of throttling) which need to be requested again.
+struct BatchGetOutcome {
+    items: Vec<DynamoDbItem>,
+    unprocessed_keys: Vec<DynamoDbItem>,
+}
+
+impl DynamoDbBackedProductCatalogue {
+    async fn batch_get(&self, keys: Vec<DynamoDbItem>) -> ResolveProductsResult<BatchGetOutcome> {
+        let table_name = &self.config.table_name;
+        let mut output = self
             .dynamo_db_client
             .batch_get_item()
             .request_items(
-                "products",
+                table_name,
                 KeysAndAttributes::builder()
                     .set_keys(Some(keys))
+                    .consistent_read(self.config.consistent_read)
                     .build()
                     .unwrap(),
             )
             .send()
-            .await;
+            .await
+            .map_err(|e| ResolvePcdbProductsError::AccessError(Box::new(e.into())))?;
+
+        Ok(BatchGetOutcome {
+            items: output
+                .responses
+                .as_mut()
+                .and_then(|responses| responses.remove(table_name))
+                .unwrap_or_default(),
+            unprocessed_keys: output
+                .unprocessed_keys
+                .as_mut()
+                .and_then(|unprocessed_keys| unprocessed_keys.remove(table_name))
+                .map(|keys_and_attributes| keys_and_attributes.keys)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Gets the items for all the given keys, splitting them into requests within DynamoDB's size limit
+/// and re-requesting any unprocessed keys with exponential backoff.
+async fn batch_get_all<F, Fut>(
+    keys: Vec<DynamoDbItem>,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    mut batch_get: F,
+) -> ResolveProductsResult<Vec<DynamoDbItem>>
+where
+    F: FnMut(Vec<DynamoDbItem>) -> Fut,
+    Fut: Future<Output = ResolveProductsResult<BatchGetOutcome>>,
+{
+    let mut items = Vec::with_capacity(keys.len());
 
-        let results = match results {
-            Ok(results) => results,
-            Err(e) => {
-                return Err(ResolvePcdbProductsError::AccessError(Box::new(e.into())));
+    for chunk in keys.chunks(MAX_KEYS_PER_BATCH_GET) {
+        let mut pending_keys = chunk.to_vec();
+        let mut retries = 0;
+
+        loop {
+            let outcome = batch_get(pending_keys).await?;
+            items.extend(outcome.items);
+            pending_keys = outcome.unprocessed_keys;
+
+            if pending_keys.is_empty() {
+                break;
+            }
+            if retries == max_retries {
+                return Err(ResolvePcdbProductsError::AccessThrottled(
+                    pending_keys.len(),
+                ));
             }
-        };
 
-        let products = results.responses().unwrap().get("products").unwrap();
-        if products.len() != product_references.len() {
+            tokio::time::sleep(retry_base_delay * 2u32.saturating_pow(retries)).await;
+            retries += 1;
+        }
+    }
+
+    Ok(items)
+}
+
+impl ProductCatalogue for DynamoDbBackedProductCatalogue {
+    async fn find_products_for_references(
+        &self,
+        product_references: &[String],
+    ) -> ResolveProductsResult<HashMap<String, Product>> {
+        if product_references.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // DynamoDB rejects batches containing the same key more than once
+        let refs_from_request: HashSet<&str> =
+            product_references.iter().map(|r| r.as_str()).collect();
+        let keys: Vec<DynamoDbItem> = refs_from_request
+            .iter()
+            .map(|product_ref| self.config.key_for(product_ref))
+            .collect();
+
+        let items = batch_get_all(
+            keys,
+            self.config.max_retries,
+            self.config.retry_base_delay,
+            |keys| self.batch_get(keys),
+        )
+        .await?;
+
+        if items.len() != refs_from_request.len() {
             // there's an anomaly so work out what's missing
-            let refs_from_database: HashSet<&str> = products
+            let refs_from_database: HashSet<&str> = items
                 .iter()
-                .filter_map(|p| p.get("id").and_then(|p| p.as_s().ok().map(|r| r.as_str())))
+                .filter_map(|p| {
+                    p.get(&self.config.key_attribute)
+                        .and_then(|p| p.as_s().ok().map(|r| r.as_str()))
+                })
                 .collect();
-            let refs_from_request: HashSet<&str> =
-                product_references.iter().map(|r| r.as_str()).collect();
 
             return Err(ResolvePcdbProductsError::UnknownProductReferences(
                 refs_from_request
@@ -645,20 +991,131 @@ impl ProductCatalogue for DynamoDbBackedProductCatalogue<'_> {
             ));
         }
 
-        let products = products
-            .iter()
-            .cloned()
-            .map(|item| {
-                let product = from_item::<_, Product>(item);
-                let product = match product {
-                    Ok(product) => product,
-                    Err(e) => return Err(e),
-                };
-
-                Ok((String::from(product.id.to_string()), product))
-            })
-            .collect::<Result<HashMap<_, _>, _>>();
-
-        products.map_err(ResolvePcdbProductsError::DeserializeError)
+        products_by_key(items, &self.config.key_attribute)
+    }
+}
+
+/// Deserializes the items into products, keyed by the value of the key attribute they were
+/// requested by (which need not be the product's `id`).
+fn products_by_key(
+    items: Vec<DynamoDbItem>,
+    key_attribute: &str,
+) -> ResolveProductsResult<HashMap<String, Product>> {
+    items
+        .into_iter()
+        .map(|item| {
+            let key = item
+                .get(key_attribute)
+                .and_then(|key| key.as_s().ok())
+                .map(|key| String::from(key.as_str()));
+            let product = from_item::<_, Product>(item)
+                .map_err(ResolvePcdbProductsError::DeserializeError)?;
+            let key = key.ok_or_else(|| {
+                ResolvePcdbProductsError::InvalidProduct(
+                    product.id.to_string(),
+                    "record has no string value for the catalogue's key attribute",
+                )
+            })?;
+
+            Ok((key, product))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    fn keys(count: usize) -> Vec<DynamoDbItem> {
+        (0..count)
+            .map(|i| DynamoDbCatalogueConfig::default().key_for(&format!("product_{i}")))
+            .collect()
+    }
+
+    #[test]
+    fn test_products_are_keyed_by_the_configured_key_attribute() {
+        let item = HashMap::from([
+            ("pk".to_string(), AttributeValue::S("PRODUCT#123".into())),
+            ("id".to_string(), AttributeValue::S("123".into())),
+            (
+                "technologyType".to_string(),
+                AttributeValue::S("AirPoweredShowers".into()),
+            ),
+            ("allowLowFlowrate".to_string(), AttributeValue::Bool(false)),
+            ("flowrate".to_string(), AttributeValue::N("10".into())),
+        ]);
+
+        let products = products_by_key(vec![item], "pk").unwrap();
+
+        assert_eq!(
+            products.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["PRODUCT#123"]
+        );
+        assert_eq!(products["PRODUCT#123"].id(), "123");
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_all_splits_keys_into_batches_within_limit() {
+        let mut batch_sizes = vec![];
+
+        let items = batch_get_all(keys(250), 0, Duration::ZERO, |keys| {
+            batch_sizes.push(keys.len());
+            async move {
+                Ok(BatchGetOutcome {
+                    items: keys,
+                    unprocessed_keys: vec![],
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(items.len(), 250);
+        assert_eq!(batch_sizes, [100, 100, 50]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_all_retries_unprocessed_keys() {
+        let mut batch_sizes = vec![];
+
+        let items = batch_get_all(keys(10), 3, Duration::ZERO, |mut keys| {
+            batch_sizes.push(keys.len());
+            // process only half of each request, as DynamoDB might when throttling
+            let unprocessed_keys = keys.split_off(keys.len().div_ceil(2));
+            async move {
+                Ok(BatchGetOutcome {
+                    items: keys,
+                    unprocessed_keys,
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(items.len(), 10);
+        assert_eq!(batch_sizes, [10, 5, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_all_reports_throttling_once_retries_are_exhausted() {
+        let mut outcomes = VecDeque::from([3, 2, 2]);
+
+        let result = batch_get_all(keys(4), 2, Duration::ZERO, |mut keys| {
+            let unprocessed_count = outcomes.pop_front().unwrap();
+            let unprocessed_keys = keys.split_off(keys.len() - unprocessed_count);
+            async move {
+                Ok(BatchGetOutcome {
+                    items: keys,
+                    unprocessed_keys,
+                })
+            }
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::AccessThrottled(2))
+        ));
     }
 }
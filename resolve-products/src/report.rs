@@ -0,0 +1,327 @@
+//! module provides a report of where the values in a resolved document came from, for use in audit
+//! trails
+//!
+//! The report is built by comparing the document before and after each transform module runs, so
+//! it reflects what the transforms actually did rather than what they are expected to do.
+
+use crate::ALL_PRODUCT_REFERENCE_FIELDS;
+use crate::in_use_factors::{HotWaterOnlyInUseFactorEntry, InUseFactorsEntry, MVInUseFactorEntry};
+use crate::products::Product;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use smartstring::alias::String as SmartString;
+use std::collections::HashMap;
+
+/// An account of how the product references in a document were resolved.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionReport {
+    /// Every product reference in the input document, and the product it was resolved to.
+    pub products: Vec<ResolvedProductReference>,
+    /// The changes each transform module made to the document, in the order the modules ran.
+    pub modules: Vec<ModuleChanges>,
+    /// The in use factors that were written into the document.
+    pub in_use_factors: Vec<AppliedInUseFactor>,
+}
+
+/// A product reference in the input document and the PCDB product it referred to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedProductReference {
+    /// JSON pointer to the object within the input document that held the reference.
+    pub pointer: String,
+    /// The field the reference was held in (e.g. `product_reference`).
+    pub field: String,
+    pub product_reference: String,
+    pub product_id: String,
+    pub technology_type: String,
+    pub brand_name: Option<String>,
+    pub model_name: Option<String>,
+    pub model_qualifier: Option<String>,
+}
+
+/// The fields one transform module inserted into, removed from or changed within the document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModuleChanges {
+    pub module: String,
+    pub inserted: Vec<FieldValue>,
+    pub removed: Vec<FieldValue>,
+    pub changed: Vec<ChangedField>,
+}
+
+impl ModuleChanges {
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A field within the document, with its value at the time it was inserted or removed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldValue {
+    pub pointer: String,
+    pub value: JsonValue,
+}
+
+/// A field within the document whose value was replaced.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangedField {
+    pub pointer: String,
+    pub previous_value: JsonValue,
+    pub value: JsonValue,
+}
+
+/// An in use factor that a transform module wrote into the document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AppliedInUseFactor {
+    pub module: String,
+    pub pointer: String,
+    /// The ID of the in use factors data the factor was taken from.
+    pub entry_id: String,
+    pub value: JsonValue,
+}
+
+/// The ID of the in use factors data that the document field with the given name is taken from.
+fn in_use_factors_entry_id_for_field(field: &str) -> Option<&'static str> {
+    match field {
+        "SFP_in_use_factor" => Some(MVInUseFactorEntry::entry_id()),
+        "in_use_factor_mismatch" => Some(HotWaterOnlyInUseFactorEntry::entry_id()),
+        _ => None,
+    }
+}
+
+impl ResolutionReport {
+    pub(crate) fn record_product_references(
+        &mut self,
+        json: &JsonValue,
+        products: &HashMap<SmartString, Product>,
+    ) {
+        for (pointer, field, product_reference) in product_reference_locations(json) {
+            if let Some(product) = products.get(product_reference) {
+                self.products.push(ResolvedProductReference {
+                    pointer,
+                    field: field.to_string(),
+                    product_reference: product_reference.to_string(),
+                    product_id: product.id().to_string(),
+                    technology_type: product.technology_type().to_string(),
+                    brand_name: product.brand_name().map(ToString::to_string),
+                    model_name: product.model_name().map(ToString::to_string),
+                    model_qualifier: product.model_qualifier().map(ToString::to_string),
+                });
+            }
+        }
+    }
+
+    /// Records the changes that a transform module made, given the document from before it ran.
+    pub(crate) fn record_module_changes(
+        &mut self,
+        module: &'static str,
+        before: &JsonValue,
+        after: &JsonValue,
+    ) {
+        let mut changes = ModuleChanges {
+            module: module.to_string(),
+            inserted: vec![],
+            removed: vec![],
+            changed: vec![],
+        };
+        diff(before, after, &mut String::new(), &mut changes);
+
+        for inserted in &changes.inserted {
+            let field = inserted.pointer.rsplit('/').next().unwrap_or_default();
+            if let Some(entry_id) = in_use_factors_entry_id_for_field(field) {
+                self.in_use_factors.push(AppliedInUseFactor {
+                    module: module.to_string(),
+                    pointer: inserted.pointer.clone(),
+                    entry_id: entry_id.to_string(),
+                    value: inserted.value.clone(),
+                });
+            }
+        }
+
+        if !changes.is_empty() {
+            self.modules.push(changes);
+        }
+    }
+}
+
+/// Keeps a snapshot of the document between transform modules when a report is being made, so that
+/// the changes each module makes can be recorded.
+pub(crate) struct ModuleChangeRecorder<'a> {
+    report_and_snapshot: Option<(&'a mut ResolutionReport, JsonValue)>,
+}
+
+impl<'a> ModuleChangeRecorder<'a> {
+    pub(crate) fn new(report: Option<&'a mut ResolutionReport>, json: &JsonValue) -> Self {
+        Self {
+            report_and_snapshot: report.map(|report| (report, json.clone())),
+        }
+    }
+
+    pub(crate) fn module_finished(&mut self, module: &'static str, json: &JsonValue) {
+        if let Some((report, snapshot)) = &mut self.report_and_snapshot {
+            report.record_module_changes(module, snapshot, json);
+            if snapshot != json {
+                *snapshot = json.clone();
+            }
+        }
+    }
+}
+
+/// Lists the JSON pointers of all objects in the document that hold product references, alongside
+/// the field holding the reference and the reference itself.
+pub(crate) fn product_reference_locations(json: &JsonValue) -> Vec<(String, &'static str, &str)> {
+    fn walk<'a>(
+        json: &'a JsonValue,
+        pointer: &mut String,
+        locations: &mut Vec<(String, &'static str, &'a str)>,
+    ) {
+        match json {
+            JsonValue::Object(object) => {
+                for field in ALL_PRODUCT_REFERENCE_FIELDS {
+                    if let Some(JsonValue::String(reference)) = object.get(field) {
+                        locations.push((pointer.clone(), field, reference));
+                    }
+                }
+                for (key, value) in object {
+                    let length = pointer.len();
+                    push_pointer_token(pointer, key);
+                    walk(value, pointer, locations);
+                    pointer.truncate(length);
+                }
+            }
+            JsonValue::Array(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    let length = pointer.len();
+                    push_pointer_token(pointer, &index.to_string());
+                    walk(value, pointer, locations);
+                    pointer.truncate(length);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut locations = vec![];
+    walk(json, &mut String::new(), &mut locations);
+
+    locations
+}
+
+/// Appends a reference token to a JSON pointer, escaping it as per RFC 6901.
+pub(crate) fn push_pointer_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+fn diff(before: &JsonValue, after: &JsonValue, pointer: &mut String, changes: &mut ModuleChanges) {
+    match (before, after) {
+        (JsonValue::Object(before), JsonValue::Object(after)) => {
+            for (key, before_value) in before {
+                let length = pointer.len();
+                push_pointer_token(pointer, key);
+                match after.get(key) {
+                    Some(after_value) => diff(before_value, after_value, pointer, changes),
+                    None => changes.removed.push(FieldValue {
+                        pointer: pointer.clone(),
+                        value: before_value.clone(),
+                    }),
+                }
+                pointer.truncate(length);
+            }
+            for (key, after_value) in after {
+                if !before.contains_key(key) {
+                    let length = pointer.len();
+                    push_pointer_token(pointer, key);
+                    changes.inserted.push(FieldValue {
+                        pointer: pointer.clone(),
+                        value: after_value.clone(),
+                    });
+                    pointer.truncate(length);
+                }
+            }
+        }
+        (before, after) if before != after => changes.changed.push(ChangedField {
+            pointer: pointer.clone(),
+            previous_value: before.clone(),
+            value: after.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_product_reference_locations_are_json_pointers() {
+        let json = json!({
+            "HotWaterSource": {"hw cylinder": {"product_reference": "tank"}},
+            "HeatSourceWet": {"a/b": {"product_reference": "hp", "heat_network_reference": "hn"}},
+            "List": [{"product_reference": "shower"}, {"product_reference": 1}],
+        });
+
+        let mut locations = product_reference_locations(&json);
+        locations.sort();
+
+        assert_eq!(
+            locations,
+            [
+                ("/HeatSourceWet/a~1b".into(), "heat_network_reference", "hn"),
+                ("/HeatSourceWet/a~1b".into(), "product_reference", "hp"),
+                (
+                    "/HotWaterSource/hw cylinder".into(),
+                    "product_reference",
+                    "tank"
+                ),
+                ("/List/0".into(), "product_reference", "shower"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_changes_record_inserted_removed_and_changed_fields() {
+        let before = json!({
+            "MechanicalVentilation": {
+                "mvhr": {"product_reference": "mvhr", "vent_type": "MVHR", "SFP": 1.0}
+            }
+        });
+        let after = json!({
+            "MechanicalVentilation": {
+                "mvhr": {"vent_type": "MVHR", "SFP": 1.5, "SFP_in_use_factor": 1.25}
+            }
+        });
+        let mut report = ResolutionReport::default();
+
+        report.record_module_changes("mechanical_ventilation", &before, &after);
+        report.record_module_changes("mechanical_ventilation", &after, &after);
+
+        assert_eq!(
+            report.modules,
+            [ModuleChanges {
+                module: "mechanical_ventilation".into(),
+                inserted: vec![FieldValue {
+                    pointer: "/MechanicalVentilation/mvhr/SFP_in_use_factor".into(),
+                    value: json!(1.25),
+                }],
+                removed: vec![FieldValue {
+                    pointer: "/MechanicalVentilation/mvhr/product_reference".into(),
+                    value: json!("mvhr"),
+                }],
+                changed: vec![ChangedField {
+                    pointer: "/MechanicalVentilation/mvhr/SFP".into(),
+                    previous_value: json!(1.0),
+                    value: json!(1.5),
+                }],
+            }]
+        );
+        assert_eq!(
+            report.in_use_factors,
+            [AppliedInUseFactor {
+                module: "mechanical_ventilation".into(),
+                pointer: "/MechanicalVentilation/mvhr/SFP_in_use_factor".into(),
+                entry_id: MVInUseFactorEntry::entry_id().into(),
+                value: json!(1.25),
+            }]
+        );
+    }
+}
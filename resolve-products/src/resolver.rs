@@ -0,0 +1,424 @@
+//! module provides a long-lived resolver that holds everything needed to resolve product references,
+//! so that this work is only done once rather than for every document resolved
+
+use crate::caching::{CacheConfig, CachingInUseFactorsAccess, CachingProductCatalogue};
+use crate::conflicts::{ConflictPolicy, Conflicts, FieldConflict};
+use crate::errors::{JsonValidationError, ResolvePcdbProductsError};
+use crate::in_use_factors::InUseFactorsAccess;
+use crate::inspection::{Inspection, inspect_references};
+use crate::output::write_json_async;
+use crate::products::{ProductCatalogue, find_known_products};
+use crate::report::ResolutionReport;
+use crate::transform::{EntryErrors, transform_json};
+use crate::transformer::TransformerRegistry;
+use crate::unresolve::{resolved_pointers, restore};
+use crate::versions::HemVersions;
+use crate::warnings::{Warning, Warnings};
+use crate::{ResolveProductsResult, extract_product_references};
+use itertools::Itertools;
+use serde_json::Value as JsonValue;
+use std::fmt::Debug;
+use std::io::{BufReader, Cursor, Read, Write};
+use std::time::Duration;
+use tokio::io::AsyncWrite;
+
+/// Options for what a [`Resolver`] does beyond resolving product references.
+#[derive(Clone, Debug, Default)]
+pub struct ResolverOptions {
+    /// Whether to make a [`ResolutionReport`] of where the values in each resolved document came
+    /// from.
+    pub report: bool,
+    /// Whether to carry on resolving after an error with one entry in a document, so that all such
+    /// errors can be returned together (as [`ResolvePcdbProductsError::ResolutionErrors`]) rather
+    /// than only the first.
+    pub collect_all_errors: bool,
+    /// Whether to check each resolved document against the HEM target schema, returning
+    /// [`ResolvePcdbProductsError::InvalidResolvedDocument`] if it is not valid.
+    pub validate_output: bool,
+    /// What to do when a value given in the input differs from the value the PCDB gives for it.
+    pub conflict_policy: ConflictPolicy,
+}
+
+/// A resolved document alongside any extra information that was asked for about its resolution.
+#[derive(Clone, Debug)]
+pub struct Resolution {
+    pub document: JsonValue,
+    /// A report on the resolution, if [`ResolverOptions::report`] was set.
+    pub report: Option<ResolutionReport>,
+    /// Any non-fatal issues found while resolving the document.
+    pub warnings: Vec<Warning>,
+    /// Every value given in the input that differed from the value the PCDB gives for it, whichever
+    /// was kept.
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// Resolves product references in input documents against a product catalogue and in use factors
+/// data.
+///
+/// A resolver holds the compiled schema validators for each HEM version it can resolve documents for
+/// alongside its catalogue and in use factors access. It is `Send + Sync` when they are, so one instance can be shared (e.g. in an `Arc`)
+/// between all the tasks of a service.
+pub struct Resolver<C, A> {
+    versions: HemVersions,
+    product_catalogue: C,
+    in_use_factors_access: A,
+    options: ResolverOptions,
+    transformers: TransformerRegistry,
+}
+
+impl<C: ProductCatalogue, A: InUseFactorsAccess> Resolver<C, A> {
+    pub async fn new(product_catalogue: C, in_use_factors_access: A) -> Self {
+        Self {
+            versions: HemVersions::new().await,
+            product_catalogue,
+            in_use_factors_access,
+            options: Default::default(),
+            transformers: Default::default(),
+        }
+    }
+
+    pub fn with_options(self, options: ResolverOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Uses the given transformers in place of the built-in ones alone, e.g. to resolve references
+    /// to in-house products.
+    pub fn with_transformers(self, transformers: TransformerRegistry) -> Self {
+        Self {
+            transformers,
+            ..self
+        }
+    }
+
+    /// Resolves documents declaring one of the given HEM versions for that version, rather than only
+    /// for the version built in to this crate.
+    pub fn with_versions(self, versions: HemVersions) -> Self {
+        Self { versions, ..self }
+    }
+
+    pub fn options(&self) -> &ResolverOptions {
+        &self.options
+    }
+
+    pub fn product_catalogue(&self) -> &C {
+        &self.product_catalogue
+    }
+
+    pub fn in_use_factors_access(&self) -> &A {
+        &self.in_use_factors_access
+    }
+
+    /// Validates the given JSON against the input schema and resolves the product references in it.
+    pub async fn resolve<R: Read>(
+        &self,
+        json: R,
+    ) -> ResolveProductsResult<impl Read + Debug + use<R, C, A>> {
+        let resolution = self.resolve_with_details(json).await?;
+
+        Ok(Cursor::new(resolution.document.to_string()))
+    }
+
+    /// Validates the given document against the input schema and resolves the product references in
+    /// it, for callers that already hold the document as a [`serde_json::Value`].
+    pub async fn resolve_value(&self, json: JsonValue) -> ResolveProductsResult<JsonValue> {
+        Ok(self.resolve_value_with_details(json).await?.document)
+    }
+
+    /// As [`Resolver::resolve_value`], returning the resolved document along with whatever the
+    /// resolver's options ask for.
+    pub async fn resolve_value_with_details(
+        &self,
+        json: JsonValue,
+    ) -> ResolveProductsResult<Resolution> {
+        self.resolve_json(json, &self.product_catalogue, &self.in_use_factors_access)
+            .await
+    }
+
+    /// Validates the given JSON against the input schema, resolves the product references in it and
+    /// writes the resolved document to the given writer.
+    pub async fn resolve_to_writer(
+        &self,
+        json: impl Read,
+        writer: impl Write,
+    ) -> ResolveProductsResult<()> {
+        let resolution = self.resolve_with_details(json).await?;
+
+        serde_json::to_writer(writer, &resolution.document)
+            .map_err(|e| ResolvePcdbProductsError::WriteError(e.into()))
+    }
+
+    /// As [`Resolver::resolve_to_writer`], writing to an asynchronous writer. The resolved document
+    /// is serialized in chunks as it is written.
+    pub async fn resolve_to_async_writer(
+        &self,
+        json: impl Read,
+        writer: impl AsyncWrite + Unpin,
+    ) -> ResolveProductsResult<()> {
+        let resolution = self.resolve_with_details(json).await?;
+
+        write_json_async(resolution.document, writer)
+            .await
+            .map_err(ResolvePcdbProductsError::WriteError)
+    }
+
+    /// Validates the given JSON against the input schema and lists the product references in it,
+    /// with the products they refer to and the transformers that would resolve them, without
+    /// resolving them.
+    ///
+    /// References to products that are not in the catalogue are listed without a product, rather
+    /// than causing an error.
+    pub async fn inspect(&self, json: impl Read) -> ResolveProductsResult<Inspection> {
+        let reader = BufReader::new(json);
+
+        let input: JsonValue =
+            serde_json::from_reader(reader).map_err(|_| ResolvePcdbProductsError::InvalidJson)?;
+
+        let mut warnings = Warnings::default();
+        let version = self.versions.select(&input, &mut warnings);
+
+        if let Err(e) = version.input_schema().validate(&input) {
+            return Err(JsonValidationError::from(e).into());
+        }
+
+        let references =
+            inspect_references(&input, &self.product_catalogue, &self.transformers).await?;
+
+        Ok(Inspection {
+            hem_version: version.name().to_owned(),
+            references,
+            warnings: warnings.into_vec(),
+        })
+    }
+
+    /// Validates the given JSON against the input schema and resolves the product references in it,
+    /// returning the resolved document along with whatever the resolver's options ask for.
+    pub async fn resolve_with_details(&self, json: impl Read) -> ResolveProductsResult<Resolution> {
+        let reader = BufReader::new(json);
+
+        let input: JsonValue =
+            serde_json::from_reader(reader).map_err(|_| ResolvePcdbProductsError::InvalidJson)?;
+
+        self.resolve_json(input, &self.product_catalogue, &self.in_use_factors_access)
+            .await
+    }
+
+    /// Restores a resolved document to the form it was resolved from, given the report made when it
+    /// was resolved (see [`ResolverOptions::report`]), which may have been saved and loaded again.
+    ///
+    /// The fields that transforms inserted are removed and those they removed (such as
+    /// `product_reference`, `installation_type` and `installed_under_approved_scheme`) are put back.
+    /// The restored document is then resolved again against the current catalogue, so fails with
+    /// [`ResolvePcdbProductsError::ResolvedValuesChanged`] if any resolved field no longer has the
+    /// value it was resolved with, or would now be given a different value by the PCDB, and with
+    /// [`ResolvePcdbProductsError::UnknownProductReferences`] if a referenced product is no longer in the
+    /// catalogue.
+    pub async fn unresolve(
+        &self,
+        document: &JsonValue,
+        report: &ResolutionReport,
+    ) -> ResolveProductsResult<JsonValue> {
+        let restored = restore(document, report)?;
+
+        let resolution = self
+            .resolve_json(
+                restored.clone(),
+                &self.product_catalogue,
+                &self.in_use_factors_access,
+            )
+            .await?;
+
+        let changed_pointers = resolved_pointers(report)
+            .into_iter()
+            .filter(|pointer| document.pointer(pointer) != resolution.document.pointer(pointer))
+            .map(ToString::to_string)
+            .collect_vec();
+        if !changed_pointers.is_empty() {
+            return Err(ResolvePcdbProductsError::ResolvedValuesChanged(
+                changed_pointers,
+            ));
+        }
+
+        Ok(restored)
+    }
+
+    /// Resolves each of the given documents, looking up the products referenced across all of them
+    /// (and each kind of in use factors data they need) only once.
+    ///
+    /// Returns the result for each document in the order given, or an error if the shared lookup of
+    /// products fails for a reason other than some of them being unknown (in which case only the
+    /// documents referring to unknown products fail).
+    pub async fn resolve_batch<R: Read>(
+        &self,
+        documents: impl IntoIterator<Item = R>,
+    ) -> ResolveProductsResult<Vec<ResolveProductsResult<Resolution>>>
+    where
+        C: Sync,
+        A: Sync,
+    {
+        let documents = documents
+            .into_iter()
+            .map(|json| {
+                serde_json::from_reader::<_, JsonValue>(BufReader::new(json))
+                    .map_err(|_| ResolvePcdbProductsError::InvalidJson)
+            })
+            .collect::<Vec<_>>();
+
+        let product_references = documents
+            .iter()
+            .filter_map(|document| document.as_ref().ok())
+            .filter_map(|document| extract_product_references(document).ok())
+            .flatten()
+            .unique()
+            .collect::<Vec<_>>();
+
+        let cache_config = CacheConfig {
+            max_entries: product_references.len().max(1),
+            time_to_live: Duration::MAX,
+        };
+        let product_catalogue = CachingProductCatalogue::new(&self.product_catalogue, cache_config);
+        let in_use_factors_access =
+            CachingInUseFactorsAccess::new(&self.in_use_factors_access, cache_config);
+        find_known_products(&product_references, &product_catalogue).await?;
+
+        let mut results = Vec::with_capacity(documents.len());
+        for document in documents {
+            results.push(match document {
+                Ok(input) => {
+                    self.resolve_json(input, &product_catalogue, &in_use_factors_access)
+                        .await
+                }
+                Err(e) => Err(e),
+            });
+        }
+
+        Ok(results)
+    }
+
+    async fn resolve_json(
+        &self,
+        mut input: JsonValue,
+        product_catalogue: &impl ProductCatalogue,
+        in_use_factors_access: &impl InUseFactorsAccess,
+    ) -> ResolveProductsResult<Resolution> {
+        let mut warnings = Warnings::default();
+        let version = self.versions.select(&input, &mut warnings);
+
+        // validate first
+        if let Err(e) = version.input_schema().validate(&input) {
+            return Err(JsonValidationError::from(e).into());
+        }
+
+        let mut report = self.options.report.then(ResolutionReport::default);
+        let mut conflicts = Conflicts::new(self.options.conflict_policy);
+
+        transform_json(
+            &mut input,
+            product_catalogue,
+            in_use_factors_access,
+            report.as_mut(),
+            if self.options.collect_all_errors {
+                EntryErrors::collecting()
+            } else {
+                EntryErrors::default()
+            },
+            &mut warnings,
+            &mut conflicts,
+            &self.transformers,
+            version.behaviour(),
+        )
+        .await?;
+
+        let conflicts = conflicts.into_result()?;
+
+        if self.options.validate_output {
+            if let Err(e) = version.target_schema().validate(&input) {
+                return Err(ResolvePcdbProductsError::InvalidResolvedDocument(Box::new(
+                    e.into(),
+                )));
+            }
+        }
+
+        Ok(Resolution {
+            document: input,
+            report,
+            warnings: warnings.into_vec(),
+            conflicts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
+    use crate::transform::catalogue::FixtureBackedProductCatalogue;
+    use crate::versions::BUILT_IN_HEM_VERSION;
+
+    async fn fixture_backed_resolver()
+    -> Resolver<FixtureBackedProductCatalogue, FixtureBackedInUseFactorsAccess> {
+        Resolver::new(
+            FixtureBackedProductCatalogue::new(),
+            FixtureBackedInUseFactorsAccess,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_resolvers_share_compiled_validator() {
+        let first = fixture_backed_resolver().await;
+        let second = fixture_backed_resolver().await;
+
+        let input_schema = |resolver: &Resolver<_, _>| {
+            resolver
+                .versions
+                .get(BUILT_IN_HEM_VERSION)
+                .unwrap()
+                .input_schema() as *const _
+        };
+        assert_eq!(input_schema(&first), input_schema(&second));
+    }
+
+    #[tokio::test]
+    async fn test_resolver_rejects_input_failing_schema_check() {
+        let result = fixture_backed_resolver()
+            .await
+            .resolve(Cursor::new(r#"{"foo": "bar"}"#))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::InvalidRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolver_validating_output_accepts_valid_output_and_locates_invalid_fragments() {
+        let resolver = fixture_backed_resolver().await;
+        let input: JsonValue =
+            serde_json::from_str(include_str!("../tests/fixtures/demo_fhs.json")).unwrap();
+        let mut output = input.clone();
+        output.as_object_mut().unwrap().remove("General");
+
+        let target_schema = resolver
+            .versions
+            .get(BUILT_IN_HEM_VERSION)
+            .unwrap()
+            .target_schema();
+        assert!(target_schema.validate(&input).is_ok());
+        let Err(error) = target_schema.validate(&output) else {
+            panic!("Expected output without General to fail the target schema");
+        };
+        let error = JsonValidationError::from(error);
+        assert_eq!(error.instance_path(), "");
+        assert!(error.schema_path().contains("required"));
+
+        let resolution = resolver
+            .with_options(ResolverOptions {
+                validate_output: true,
+                ..Default::default()
+            })
+            .resolve_with_details(Cursor::new(input.to_string()))
+            .await;
+        assert!(resolution.is_ok(), "{:?}", resolution.err());
+    }
+}
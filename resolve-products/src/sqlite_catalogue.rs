@@ -0,0 +1,398 @@
+//! module provides a product catalogue held in an embedded SQLite database, for use as a fast local
+//! copy of the PCDB
+//!
+//! Each product record is stored as JSON keyed by its `id`, with the technology type and the
+//! brand/model names held in indexed columns so that products can also be looked up by those.
+//! In use factors records are stored in the same table under their entry IDs.
+
+use crate::ResolveProductsResult;
+use crate::errors::ResolvePcdbProductsError;
+use crate::in_use_factors::{InUseFactorsAccess, InUseFactorsEntry, InUseFactorsInaccessibleError};
+use crate::products::{Product, ProductCatalogue};
+use aws_sdk_dynamodb::types::AttributeValue;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use serde::Deserialize;
+use serde_dynamo::from_item;
+use serde_json::Value as JsonValue;
+use smartstring::alias::String as SmartString;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS products (
+        id TEXT PRIMARY KEY NOT NULL,
+        technology_type TEXT,
+        brand_name TEXT,
+        model_name TEXT,
+        record TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS products_technology_type ON products (technology_type);
+    CREATE INDEX IF NOT EXISTS products_brand_model ON products (brand_name, model_name);
+";
+
+/// Product catalogue and in use factors access backed by a SQLite database.
+///
+/// Lookups made while resolving run on tokio's blocking thread pool, so they do not hold up the
+/// runtime's worker threads.
+#[derive(Debug)]
+pub struct SqliteCatalogue {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteCatalogue {
+    /// Opens (or creates) a catalogue database at the given path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SqliteCatalogueError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, SqliteCatalogueError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, SqliteCatalogueError> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        lock(&self.connection)
+    }
+
+    /// Runs the given queries against the connection on the blocking thread pool.
+    async fn query<T: Send + 'static>(
+        &self,
+        queries: impl FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> rusqlite::Result<T> {
+        let connection = Arc::clone(&self.connection);
+
+        tokio::task::spawn_blocking(move || queries(&lock(&connection)))
+            .await
+            .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))
+    }
+
+    /// Imports items as they are stored in DynamoDB (for instance as read by a scan of the
+    /// `products` table), replacing any existing records with the same IDs. Returns the number of
+    /// items imported.
+    pub fn import_dynamo_items(
+        &self,
+        items: impl IntoIterator<Item = HashMap<String, AttributeValue>>,
+    ) -> Result<usize, SqliteCatalogueError> {
+        let records = items
+            .into_iter()
+            .map(from_item::<_, JsonValue>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.import_records(records)
+    }
+
+    /// Imports product records in the JSON shape used in PCDB exports, replacing any existing
+    /// records with the same IDs. Returns the number of records imported.
+    pub fn import_records(
+        &self,
+        records: impl IntoIterator<Item = JsonValue>,
+    ) -> Result<usize, SqliteCatalogueError> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        let mut count = 0;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT OR REPLACE INTO products (id, technology_type, brand_name, model_name, record)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for record in records {
+                let field = |name: &str| record.get(name).and_then(JsonValue::as_str);
+                let id = field("id")
+                    .ok_or_else(|| SqliteCatalogueError::RecordWithoutId(record.clone()))?;
+                statement.execute(params![
+                    id,
+                    field("technologyType"),
+                    field("brandName"),
+                    field("modelName"),
+                    record.to_string(),
+                ])?;
+                count += 1;
+            }
+        }
+        transaction.commit()?;
+
+        Ok(count)
+    }
+
+    /// Lists the IDs of all products of the given technology type (e.g. "AirSourceHeatPump").
+    pub fn product_ids_for_technology_type(
+        &self,
+        technology_type: &str,
+    ) -> Result<Vec<String>, SqliteCatalogueError> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare_cached("SELECT id FROM products WHERE technology_type = ?1 ORDER BY id")?;
+        let ids = statement
+            .query_map([technology_type], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        Ok(ids)
+    }
+
+    /// Lists the IDs of all products with the given brand and model names.
+    pub fn product_ids_for_brand_and_model(
+        &self,
+        brand_name: &str,
+        model_name: &str,
+    ) -> Result<Vec<String>, SqliteCatalogueError> {
+        let connection = self.connection();
+        let mut statement = connection.prepare_cached(
+            "SELECT id FROM products WHERE brand_name = ?1 AND model_name = ?2 ORDER BY id",
+        )?;
+        let ids = statement
+            .query_map([brand_name, model_name], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        Ok(ids)
+    }
+
+    async fn record(&self, id: &str) -> rusqlite::Result<Option<String>> {
+        let id = id.to_owned();
+
+        self.query(move |connection| {
+            connection
+                .prepare_cached("SELECT record FROM products WHERE id = ?1")?
+                .query_row([id], |row| row.get(0))
+                .optional()
+        })
+        .await
+    }
+
+    /// Fetches the records with the given IDs in a single query, keyed by ID.
+    async fn records(&self, ids: Vec<String>) -> rusqlite::Result<HashMap<String, String>> {
+        self.query(move |connection| {
+            let placeholders = vec!["?"; ids.len()].join(", ");
+            connection
+                .prepare(&format!(
+                    "SELECT id, record FROM products WHERE id IN ({placeholders})"
+                ))?
+                .query_map(params_from_iter(&ids), |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect()
+        })
+        .await
+    }
+}
+
+fn lock(connection: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+    // a panic while holding the lock cannot leave the connection in a state we need to care about
+    connection
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl ProductCatalogue for SqliteCatalogue {
+    async fn find_products_for_references(
+        &self,
+        product_references: &[SmartString],
+    ) -> ResolveProductsResult<HashMap<SmartString, Product>> {
+        let records = self
+            .records(product_references.iter().map(ToString::to_string).collect())
+            .await
+            .map_err(|e| ResolvePcdbProductsError::SqliteAccessError(Box::new(e)))?;
+        let mut missing_product_references = vec![];
+        let mut products = HashMap::with_capacity(product_references.len());
+
+        for product_reference in product_references {
+            match records.get(product_reference.as_str()) {
+                Some(record) => {
+                    let product = serde_json::from_str(record)
+                        .map_err(ResolvePcdbProductsError::JsonDeserializeError)?;
+                    products.insert(product_reference.clone(), product);
+                }
+                None => missing_product_references.push(product_reference.to_string()),
+            }
+        }
+
+        if !missing_product_references.is_empty() {
+            return Err(ResolvePcdbProductsError::UnknownProductReferences(
+                missing_product_references.into(),
+            ));
+        }
+
+        Ok(products)
+    }
+}
+
+impl InUseFactorsAccess for SqliteCatalogue {
+    async fn in_use_factors<T: InUseFactorsEntry>(
+        &self,
+    ) -> Result<Vec<T>, InUseFactorsInaccessibleError> {
+        let record = self
+            .record(T::entry_id())
+            .await
+            .map_err(|e| InUseFactorsInaccessibleError::SqliteError(Box::new(e)))?
+            .ok_or(InUseFactorsInaccessibleError::DataKeyMissingFromInUseFactorsRecord)?;
+        let record: JsonValue = serde_json::from_str(&record)
+            .map_err(|_| InUseFactorsInaccessibleError::JsonDeserializeError(record.into()))?;
+        let data = record
+            .get("data")
+            .ok_or(InUseFactorsInaccessibleError::DataKeyMissingFromInUseFactorsRecord)?;
+
+        Vec::<T>::deserialize(data)
+            .map_err(|_| InUseFactorsInaccessibleError::JsonDeserializeError(data.clone()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SqliteCatalogueError {
+    #[error("Error encountered while accessing SQLite catalogue: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Could not convert DynamoDB item into a product record: {0}")]
+    DynamoItem(#[from] serde_dynamo::Error),
+    #[error("Product record did not have a string 'id' field: {0}")]
+    RecordWithoutId(JsonValue),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_use_factors::MVInUseFactorEntry;
+    use crate::products::Technology;
+    use rstest::{fixture, rstest};
+    use serde_dynamo::to_item;
+
+    fn pcdb_records() -> Vec<JsonValue> {
+        let records: serde_json::Map<String, JsonValue> =
+            serde_json::from_str(include_str!("../tests/fixtures/pcdb_products.json")).unwrap();
+        records.into_iter().map(|(_, record)| record).collect()
+    }
+
+    #[fixture]
+    fn catalogue() -> SqliteCatalogue {
+        let catalogue = SqliteCatalogue::open_in_memory().unwrap();
+        let items = pcdb_records()
+            .into_iter()
+            .map(|record| to_item(record).unwrap());
+        catalogue.import_dynamo_items(items).unwrap();
+        catalogue
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_finds_products_imported_from_dynamo_items(catalogue: SqliteCatalogue) {
+        let products = catalogue
+            .find_products_for_references(&["hp".into(), "smart_tank".into()])
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            products["hp"].technology,
+            Technology::HeatPump { .. }
+        ));
+        assert!(matches!(
+            products["smart_tank"].technology,
+            Technology::SmartHotWaterTank { .. }
+        ));
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_unknown_product_references_are_all_reported(catalogue: SqliteCatalogue) {
+        let result = catalogue
+            .find_products_for_references(&["hp".into(), "nope".into(), "nada".into()])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::UnknownProductReferences(ref references)) if references.len() == 2
+        ));
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_badly_formed_product_errors(catalogue: SqliteCatalogue) {
+        let result = catalogue
+            .find_products_for_references(&["hiu_missing_technology_type".into()])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::JsonDeserializeError(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_provides_in_use_factors(catalogue: SqliteCatalogue) {
+        assert!(
+            !catalogue
+                .in_use_factors::<MVInUseFactorEntry>()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_unparseable_in_use_factors_record_errors(catalogue: SqliteCatalogue) {
+        catalogue
+            .connection()
+            .execute(
+                "UPDATE products SET record = ?1 WHERE id = ?2",
+                ["{not json", MVInUseFactorEntry::entry_id()],
+            )
+            .unwrap();
+
+        let result = catalogue.in_use_factors::<MVInUseFactorEntry>().await;
+
+        assert!(matches!(
+            result,
+            Err(InUseFactorsInaccessibleError::JsonDeserializeError(JsonValue::String(ref record)))
+                if record == "{not json"
+        ));
+    }
+
+    #[rstest]
+    fn test_looks_up_by_indexed_columns(catalogue: SqliteCatalogue) {
+        assert_eq!(
+            catalogue
+                .product_ids_for_technology_type("SmartHotWaterTank")
+                .unwrap(),
+            ["smart_tank", "smart_tank_no_heat_exchanger_area"]
+        );
+
+        let hp = pcdb_records()
+            .into_iter()
+            .find(|record| record["id"] == "hp")
+            .unwrap();
+        assert_eq!(
+            catalogue
+                .product_ids_for_brand_and_model(
+                    hp["brandName"].as_str().unwrap(),
+                    hp["modelName"].as_str().unwrap()
+                )
+                .unwrap(),
+            ["hp"]
+        );
+    }
+
+    #[rstest]
+    fn test_reimporting_replaces_records(catalogue: SqliteCatalogue) {
+        catalogue
+            .import_records([serde_json::json!({"id": "hp", "technologyType": "Replaced"})])
+            .unwrap();
+
+        assert!(
+            catalogue
+                .product_ids_for_technology_type("AirSourceHeatPump")
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            catalogue
+                .product_ids_for_technology_type("Replaced")
+                .unwrap(),
+            ["hp"]
+        );
+    }
+}
@@ -1,49 +1,94 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
 use crate::products::{Product, Technology};
 use crate::transform::{
-    InvalidProductCategoryError, ResolveProductsResult, product_reference_from_json_object,
+    EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json, json_pointer,
+    product_reference_from_json_object,
 };
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
 use serde_json::{Value as JsonValue, json};
 use smartstring::alias::String;
 use std::collections::HashMap;
 
+/// Resolves air powered shower products on mixer showers.
+pub(crate) struct AirPoweredShowerTransformer;
+
+impl Transformer for AirPoweredShowerTransformer {
+    fn name(&self) -> &'static str {
+        "air_powered_shower"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![TransformerInfo::new(
+            self.name(),
+            "/HotWaterDemand/Shower/*",
+            Some("MixerShower"),
+            &["AirPoweredShowers"],
+        )]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(
+            async move { transform(json, context.products, context.errors, context.conflicts) },
+        )
+    }
+}
+
 pub(crate) fn transform(
     json: &mut JsonValue,
     products: &HashMap<String, Product>,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
 ) -> ResolveProductsResult<()> {
     let showers = match json.pointer_mut("/HotWaterDemand/Shower") {
         Some(node) if node.is_object() => node.as_object_mut().unwrap(),
         _ => return Ok(()),
     };
 
-    for value in showers.values_mut() {
+    for (name, value) in showers.iter_mut() {
         if let JsonValue::Object(shower) = value {
-            if let Some(shower_type) = shower.get("type").and_then(|v| v.as_str()) {
-                if matches!(shower_type, "MixerShower")
-                    && shower.contains_key(PRODUCT_REFERENCE_FIELD)
-                {
-                    let product_reference = product_reference_from_json_object(shower)?;
-                    let product = &products[&product_reference];
-
-                    if let Technology::AirPoweredShower {
-                        flow_rate,
-                        allow_low_flowrate,
-                        ..
-                    } = &product.technology
-                    {
-                        shower.insert("flowrate".into(), flow_rate.as_f64().into());
-                        shower.insert("allow_low_flowrate".into(), json!(allow_low_flowrate));
-
-                        // now remove product reference
-                        shower.remove(PRODUCT_REFERENCE_FIELD);
-                    } else {
-                        return Err(InvalidProductCategoryError::from((
-                            product_reference,
-                            "air powered shower",
-                        ))
-                        .into());
-                    }
-                }
+            let pointer = json_pointer(["HotWaterDemand", "Shower", name]);
+            let result = transform_shower(
+                &mut ResolvedEntry::new(shower, pointer.as_str(), conflicts),
+                products,
+            );
+            errors.check(|| pointer, result)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn transform_shower(
+    shower: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+) -> ResolveProductsResult<()> {
+    if let Some(shower_type) = shower.get("type").and_then(|v| v.as_str()) {
+        if matches!(shower_type, "MixerShower") && shower.contains_key(PRODUCT_REFERENCE_FIELD) {
+            let product_reference = product_reference_from_json_object(shower)?;
+            let product = &products[&product_reference];
+
+            if let Technology::AirPoweredShower {
+                flow_rate,
+                allow_low_flowrate,
+                ..
+            } = &product.technology
+            {
+                shower.insert("flowrate".into(), decimal_json(flow_rate));
+                shower.insert("allow_low_flowrate".into(), json!(allow_low_flowrate));
+
+                // now remove product reference
+                shower.remove(PRODUCT_REFERENCE_FIELD);
+            } else {
+                return Err(InvalidProductCategoryError::from((
+                    product_reference,
+                    "air powered shower",
+                ))
+                .into());
             }
         }
     }
@@ -54,6 +99,7 @@ pub(crate) fn transform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transform::catalogue::assert_json_eq;
     use serde_json::{from_str, json};
 
     fn input(product_reference: &str) -> JsonValue {
@@ -82,16 +128,12 @@ mod tests {
         let result = transform(
             &mut input,
             &HashMap::from([(product_reference.into(), pcdb_shower)]),
+            &mut Default::default(),
+            &mut Default::default(),
         );
 
         assert!(result.is_ok());
-        assert_eq!(
-            input,
-            expected,
-            "actual: {}\nexpected: {}",
-            serde_json::to_string_pretty(&input).unwrap(),
-            serde_json::to_string_pretty(&expected).unwrap()
-        );
+        assert_json_eq(&input, &expected);
     }
 
     #[test]
@@ -101,7 +143,12 @@ mod tests {
         let pcdb_hps: HashMap<String, Product> =
             from_str(include_str!("fixtures/heat_pump_pcdb.json")).unwrap();
 
-        let result = transform(&mut input, &pcdb_hps);
+        let result = transform(
+            &mut input,
+            &pcdb_hps,
+            &mut Default::default(),
+            &mut Default::default(),
+        );
 
         assert!(result.is_err());
         assert!(
@@ -0,0 +1,172 @@
+use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
+use crate::products::{Product, Technology};
+use crate::transform::{
+    EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json, json_pointer,
+    product_reference_from_json_object,
+};
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
+use serde_json::Value as JsonValue;
+use smartstring::alias::String;
+use std::collections::HashMap;
+
+/// Resolves electric battery products on energy supplies.
+pub(crate) struct ElectricBatteryTransformer;
+
+impl Transformer for ElectricBatteryTransformer {
+    fn name(&self) -> &'static str {
+        "electric_battery"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![TransformerInfo::new(
+            self.name(),
+            "/EnergySupply/*/ElectricBattery",
+            None,
+            &["ElectricBattery"],
+        )]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(
+            async move { transform(json, context.products, context.errors, context.conflicts) },
+        )
+    }
+}
+
+pub(crate) fn transform(
+    json: &mut JsonValue,
+    products: &HashMap<String, Product>,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
+) -> ResolveProductsResult<()> {
+    let energy_supplies = match json.pointer_mut("/EnergySupply") {
+        Some(node) if node.is_object() => node.as_object_mut().unwrap(),
+        _ => return Ok(()),
+    };
+
+    for (name, energy_supply) in energy_supplies.iter_mut() {
+        if let Some(JsonValue::Object(battery)) = energy_supply.get_mut("ElectricBattery") {
+            let pointer = json_pointer(["EnergySupply", name, "ElectricBattery"]);
+            let result = transform_battery(
+                &mut ResolvedEntry::new(battery, pointer.as_str(), conflicts),
+                products,
+            );
+            errors.check(|| pointer, result)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn transform_battery(
+    battery: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+) -> ResolveProductsResult<()> {
+    if !battery.contains_key(PRODUCT_REFERENCE_FIELD) {
+        return Ok(());
+    }
+
+    let product_reference = product_reference_from_json_object(battery)?;
+    let product = &products[&product_reference];
+
+    if let Technology::ElectricBattery {
+        capacity,
+        charge_discharge_efficiency_round_trip,
+        minimum_charge_rate,
+        maximum_charge_rate,
+        maximum_discharge_rate,
+    } = &product.technology
+    {
+        battery.insert("capacity".into(), decimal_json(capacity));
+        battery.insert(
+            "charge_discharge_efficiency_round_trip".into(),
+            decimal_json(charge_discharge_efficiency_round_trip),
+        );
+        battery.insert(
+            "minimum_charge_rate_one_way_trip".into(),
+            decimal_json(minimum_charge_rate),
+        );
+        battery.insert(
+            "maximum_charge_rate_one_way_trip".into(),
+            decimal_json(maximum_charge_rate),
+        );
+        battery.insert(
+            "maximum_discharge_rate_one_way_trip".into(),
+            decimal_json(maximum_discharge_rate),
+        );
+
+        // now remove product reference
+        battery.remove(PRODUCT_REFERENCE_FIELD);
+    } else {
+        return Err(
+            InvalidProductCategoryError::from((product_reference, "electric battery")).into(),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::catalogue::assert_json_eq;
+    use serde_json::{from_str, json};
+
+    fn input(product_reference: &str) -> JsonValue {
+        json!({
+            "EnergySupply": {
+                "mains elec": {
+                    "fuel": "electricity",
+                    "is_export_capable": true,
+                    "ElectricBattery": {
+                        "product_reference": product_reference,
+                        "battery_location": "inside"
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_transform_electric_battery() {
+        let product_reference = "battery";
+        let mut input = input(product_reference);
+        let expected: JsonValue =
+            from_str(include_str!("fixtures/electric_battery_transformed.json")).unwrap();
+        let pcdb_battery: Product =
+            from_str(include_str!("fixtures/electric_battery_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &HashMap::from([(product_reference.into(), pcdb_battery)]),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert_json_eq(&input, &expected);
+    }
+
+    #[test]
+    fn test_transform_electric_battery_errors_when_product_type_mismatch() {
+        let product_reference = "hp";
+        let mut input = input(product_reference);
+        let pcdb_hps: HashMap<String, Product> =
+            from_str(include_str!("fixtures/heat_pump_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &pcdb_hps,
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("electric battery"));
+    }
+}
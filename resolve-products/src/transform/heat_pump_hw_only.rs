@@ -1,122 +1,179 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
 use crate::errors::ResolvePcdbProductsError;
-use crate::in_use_factors::{HotWaterOnlyInUseFactorEntry, InUseFactorsAccess};
+use crate::in_use_factors::DynInUseFactorsAccess;
 use crate::products::{HeatPumpVesselType, Product, TappingProfile, Technology};
 use crate::transform::{
-    EnergySupplies, InvalidProductCategoryError, ResolveProductsResult,
-    product_reference_from_json_object,
+    EnergySupplies, EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+    json_pointer, product_reference_from_json_object,
 };
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
 use serde_json::{Value as JsonValue, json};
 use smartstring::alias::String;
 use std::collections::HashMap;
 
+/// Resolves hot water only heat pump products on the heat sources of the hot water cylinder.
+pub(crate) struct HeatPumpHotWaterOnlyTransformer;
+
+impl Transformer for HeatPumpHotWaterOnlyTransformer {
+    fn name(&self) -> &'static str {
+        "heat_pump_hw_only"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![TransformerInfo::new(
+            self.name(),
+            "/HotWaterSource/hw cylinder/HeatSource/*",
+            Some("HeatPump_HWOnly"),
+            &["HotWaterOnlyHeatPump"],
+        )]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(async move {
+            transform(
+                json,
+                context.products,
+                context.in_use_factors_access,
+                context.energy_supplies,
+                context.errors,
+                context.conflicts,
+            )
+            .await
+        })
+    }
+}
+
 pub async fn transform(
     json: &mut JsonValue,
     products: &HashMap<String, Product>,
-    in_use_factors_access: &impl InUseFactorsAccess,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
     energy_supplies: &EnergySupplies,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
 ) -> ResolveProductsResult<()> {
     let heat_sources = match json.pointer_mut("/HotWaterSource/hw cylinder/HeatSource") {
         Some(node) if node.is_object() => node.as_object_mut().unwrap(),
         _ => return Ok(()),
     };
 
-    for value in heat_sources.values_mut() {
+    for (name, value) in heat_sources.iter_mut() {
         if let JsonValue::Object(heat_source) = value {
-            if let Some(heat_source_type) = heat_source.get("type").and_then(|v| v.as_str()) {
-                if matches!(heat_source_type, "HeatPump_HWOnly")
-                    && heat_source.contains_key(PRODUCT_REFERENCE_FIELD)
+            let pointer = json_pointer(["HotWaterSource", "hw cylinder", "HeatSource", name]);
+            let result = transform_heat_source(
+                &mut ResolvedEntry::new(heat_source, pointer.as_str(), conflicts),
+                products,
+                in_use_factors_access,
+                energy_supplies,
+            )
+            .await;
+            errors.check(|| pointer, result)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn transform_heat_source(
+    heat_source: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
+    energy_supplies: &EnergySupplies,
+) -> ResolveProductsResult<()> {
+    if let Some(heat_source_type) = heat_source.get("type").and_then(|v| v.as_str()) {
+        if matches!(heat_source_type, "HeatPump_HWOnly")
+            && heat_source.contains_key(PRODUCT_REFERENCE_FIELD)
+        {
+            let product_reference = product_reference_from_json_object(heat_source)?;
+            let product = &products[&product_reference];
+
+            if let Technology::HeatPumpHotWaterOnly {
+                power_max,
+                tank_volume_declared,
+                daily_losses_declared,
+                heat_exchanger_surface_area_declared,
+                test_data,
+                hw_vessel_loss_daily,
+                vessel_type,
+                fuel,
+                ..
+            } = &product.technology
+            {
+                let energy_supply = energy_supplies
+                    .get(fuel)
+                    .ok_or_else(|| ResolvePcdbProductsError::from(fuel))?;
+                heat_source.insert("EnergySupply".into(), json!(energy_supply.as_ref()));
+                heat_source.insert("power_max".into(), decimal_json(power_max));
+                heat_source.insert(
+                    "tank_volume_declared".into(),
+                    decimal_json(tank_volume_declared),
+                );
+                heat_source.insert(
+                    "daily_losses_declared".into(),
+                    decimal_json(daily_losses_declared),
+                );
+                if let Some(heat_exchanger_surface_area_declared) =
+                    heat_exchanger_surface_area_declared
                 {
-                    let product_reference = product_reference_from_json_object(heat_source)?;
-                    let product = &products[&product_reference];
-
-                    if let Technology::HeatPumpHotWaterOnly {
-                        power_max,
-                        tank_volume_declared,
-                        daily_losses_declared,
-                        heat_exchanger_surface_area_declared,
-                        test_data,
-                        hw_vessel_loss_daily,
-                        vessel_type,
-                        fuel,
-                        ..
-                    } = &product.technology
-                    {
-                        let energy_supply = energy_supplies
-                            .get(fuel)
-                            .ok_or_else(|| ResolvePcdbProductsError::from(fuel))?;
-                        heat_source.insert("EnergySupply".into(), json!(energy_supply.as_ref()));
-                        heat_source.insert("power_max".into(), power_max.as_f64().into());
-                        heat_source.insert(
-                            "tank_volume_declared".into(),
-                            tank_volume_declared.as_f64().into(),
-                        );
-                        heat_source.insert(
-                            "daily_losses_declared".into(),
-                            daily_losses_declared.as_f64().into(),
-                        );
-                        if let Some(heat_exchanger_surface_area_declared) =
-                            heat_exchanger_surface_area_declared
-                        {
-                            heat_source.insert(
-                                "heat_exchanger_surface_area_declared".into(),
-                                heat_exchanger_surface_area_declared.as_f64().into(),
-                            );
-                        }
-                        heat_source.insert(
-                            "test_data".into(),
-                            test_data
-                                .iter()
-                                .map(|datum| {
-                                    let tapping_profile = match datum.tapping_profile {
-                                        TappingProfile::L => "L",
-                                        TappingProfile::M => "M",
-                                    };
-                                    (
-                                        tapping_profile,
-                                        json!({
-                                            "cop_dhw": datum.cop_dhw.as_f64(),
-                                            "hw_tapping_prof_daily_total": datum.hw_tapping_prof_daily_total.as_f64(),
-                                            "energy_input_measured": datum.energy_input_measured.as_f64(),
-                                            "power_standby": datum.power_standby.as_f64(),
-                                            "hw_vessel_loss_daily": hw_vessel_loss_daily.as_f64(),
-                                        })
-                                    )
-                                })
-                                .collect(),
-                        );
-
-                        let hot_water_in_use_factors = in_use_factors_access
-                            .in_use_factors::<HotWaterOnlyInUseFactorEntry>()
-                            .await?;
-
-                        let in_use_factor_mismatch = hot_water_in_use_factors
-                            .iter()
-                            .find(|entry| {
-                                Option::<HeatPumpVesselType>::try_from(*entry)
-                                    .ok()
-                                    .flatten()
-                                    .is_some_and(|entry_vessel_type| {
-                                        entry_vessel_type == *vessel_type
-                                    })
-                            })
-                            .ok_or_else(|| ResolvePcdbProductsError::InUseFactorEntryMissingError)?
-                            .in_use_factor_mismatch;
-                        heat_source.insert(
-                            "in_use_factor_mismatch".into(),
-                            in_use_factor_mismatch.as_f64().into(),
-                        );
-
-                        // now remove product reference
-                        heat_source.remove(PRODUCT_REFERENCE_FIELD);
-                    } else {
-                        return Err(InvalidProductCategoryError::from((
-                            product_reference,
-                            "hot water only heat pump",
-                        ))
-                        .into());
-                    }
+                    heat_source.insert(
+                        "heat_exchanger_surface_area_declared".into(),
+                        decimal_json(heat_exchanger_surface_area_declared),
+                    );
                 }
+                heat_source.insert(
+                    "test_data".into(),
+                    test_data
+                        .iter()
+                        .map(|datum| {
+                            let tapping_profile = match datum.tapping_profile {
+                                TappingProfile::L => "L",
+                                TappingProfile::M => "M",
+                            };
+                            (
+                                tapping_profile,
+                                json!({
+                                    "cop_dhw": decimal_json(&datum.cop_dhw),
+                                    "hw_tapping_prof_daily_total": decimal_json(&datum.hw_tapping_prof_daily_total),
+                                    "energy_input_measured": decimal_json(&datum.energy_input_measured),
+                                    "power_standby": decimal_json(&datum.power_standby),
+                                    "hw_vessel_loss_daily": decimal_json(hw_vessel_loss_daily),
+                                })
+                            )
+                        })
+                        .collect(),
+                );
+
+                let hot_water_in_use_factors = in_use_factors_access
+                    .hot_water_only_in_use_factors()
+                    .await?;
+
+                let in_use_factor_mismatch = hot_water_in_use_factors
+                    .iter()
+                    .find(|entry| {
+                        Option::<HeatPumpVesselType>::try_from(*entry)
+                            .ok()
+                            .flatten()
+                            .is_some_and(|entry_vessel_type| entry_vessel_type == *vessel_type)
+                    })
+                    .ok_or_else(|| ResolvePcdbProductsError::InUseFactorEntryMissingError)?
+                    .in_use_factor_mismatch;
+                heat_source.insert(
+                    "in_use_factor_mismatch".into(),
+                    decimal_json(&in_use_factor_mismatch),
+                );
+
+                // now remove product reference
+                heat_source.remove(PRODUCT_REFERENCE_FIELD);
+            } else {
+                return Err(InvalidProductCategoryError::from((
+                    product_reference,
+                    "hot water only heat pump",
+                ))
+                .into());
             }
         }
     }
@@ -127,8 +184,9 @@ pub async fn transform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::in_use_factors::InUseFactorsAccess;
     use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
-    use crate::transform::catalogue::mock_energy_supplies;
+    use crate::transform::catalogue::{assert_json_eq, mock_energy_supplies};
     use rstest::*;
     use serde_json::{from_str, json};
     use std::collections::HashMap;
@@ -177,17 +235,13 @@ mod tests {
             &HashMap::from([(product_reference.into(), pcdb_hp_hw_only)]),
             &in_use_factors_access,
             &energy_supplies,
+            &mut Default::default(),
+            &mut Default::default(),
         )
         .await;
 
         assert!(result.is_ok());
-        assert_eq!(
-            input,
-            expected,
-            "actual: {}\nexpected: {}",
-            serde_json::to_string_pretty(&input).unwrap(),
-            serde_json::to_string_pretty(&expected).unwrap()
-        );
+        assert_json_eq(&input, &expected);
     }
 
     #[tokio::test]
@@ -206,6 +260,8 @@ mod tests {
             &pcdb_hps,
             &in_use_factors_access,
             &energy_supplies,
+            &mut Default::default(),
+            &mut Default::default(),
         )
         .await;
 
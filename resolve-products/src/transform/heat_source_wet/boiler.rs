@@ -1,12 +1,14 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{BoilerLocation, Product, Technology};
-use crate::transform::{EnergySupplies, InvalidProductCategoryError, ResolveProductsResult};
-use rust_decimal::prelude::ToPrimitive;
-use serde_json::{Map, Value as JsonValue, json};
+use crate::transform::{
+    EnergySupplies, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+};
+use serde_json::json;
 
 pub fn transform(
-    boiler: &mut Map<String, JsonValue>,
+    boiler: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     energy_supplies: &EnergySupplies,
@@ -35,31 +37,31 @@ pub fn transform(
         boiler.insert("EnergySupply".into(), json!(energy_supply.as_ref()));
         boiler.insert("EnergySupply_aux".into(), json!(energy_supply_aux.as_ref()));
 
-        boiler.insert("rated_power".into(), rated_power.to_f64().into());
+        boiler.insert("rated_power".into(), decimal_json(rated_power));
         boiler.insert(
             "efficiency_full_load".into(),
-            efficiency_full_load.to_f64().into(),
+            decimal_json(efficiency_full_load),
         );
         boiler.insert(
             "efficiency_part_load".into(),
-            efficiency_part_load.to_f64().into(),
+            decimal_json(efficiency_part_load),
         );
-        boiler.insert("modulation_load".into(), modulation_load.to_f64().into());
+        boiler.insert("modulation_load".into(), decimal_json(modulation_load));
         boiler.insert(
             "electricity_circ_pump".into(),
-            electricity_circ_pump.to_f64().into(),
+            decimal_json(electricity_circ_pump),
         );
         boiler.insert(
             "electricity_part_load".into(),
-            electricity_part_load.to_f64().into(),
+            decimal_json(electricity_part_load),
         );
         boiler.insert(
             "electricity_full_load".into(),
-            electricity_full_load.to_f64().into(),
+            decimal_json(electricity_full_load),
         );
         boiler.insert(
             "electricity_standby".into(),
-            electricity_standby.to_f64().into(),
+            decimal_json(electricity_standby),
         );
 
         match boiler_location {
@@ -89,7 +91,7 @@ mod tests {
     use super::*;
     use crate::transform::catalogue::{mock_energy_supplies, transformed_input_matches_expected};
     use rstest::{fixture, rstest};
-    use serde_json::{Value, json};
+    use serde_json::{Map, Value, json};
     use std::collections::HashMap;
 
     fn boiler_input(product_reference: &str, specified_location: Option<&str>) -> Value {
@@ -115,8 +117,8 @@ mod tests {
         mock_energy_supplies()
     }
 
-    fn expected_boiler_input(product_reference: &str) -> Map<String, JsonValue> {
-        let expected_boilers: JsonValue =
+    fn expected_boiler_input(product_reference: &str) -> Map<String, Value> {
+        let expected_boilers: Value =
             serde_json::from_str(include_str!("../fixtures/boiler_input_transformed.json"))
                 .unwrap();
 
@@ -141,7 +143,11 @@ mod tests {
         let pcdb_boiler = pcdb_boilers.get(product_reference).unwrap();
 
         let result = transform(
-            boiler_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                boiler_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_boiler,
             product_reference,
             &energy_supplies,
@@ -164,7 +170,7 @@ mod tests {
         let pcdb_boiler = pcdb_boilers.get(product_reference).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             pcdb_boiler,
             product_reference,
             &energy_supplies,
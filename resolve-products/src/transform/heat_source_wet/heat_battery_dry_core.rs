@@ -1,12 +1,15 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{Product, Technology};
-use crate::transform::{EnergySupplies, InvalidProductCategoryError, ResolveProductsResult};
+use crate::transform::{
+    EnergySupplies, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+};
 use itertools::Itertools;
-use serde_json::{Map, Value as JsonValue, json};
+use serde_json::{Value as JsonValue, json};
 
 pub(crate) fn transform(
-    dry_core_battery: &mut Map<String, JsonValue>,
+    dry_core_battery: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     energy_supplies: &EnergySupplies,
@@ -30,35 +33,39 @@ pub(crate) fn transform(
         dry_core_battery.insert("EnergySupply".into(), json!(energy_supply.as_ref()));
         dry_core_battery.insert(
             "electricity_circ_pump".into(),
-            electricity_circ_pump.as_f64().into(),
+            decimal_json(electricity_circ_pump),
         );
         dry_core_battery.insert(
             "electricity_standby".into(),
-            electricity_standby.as_f64().into(),
+            decimal_json(electricity_standby),
         );
-        dry_core_battery.insert("pwr_in".into(), pwr_in.as_f64().into());
+        dry_core_battery.insert("pwr_in".into(), decimal_json(pwr_in));
         dry_core_battery.insert(
             "rated_power_instant".into(),
-            rated_power_instant.as_f64().into(),
+            decimal_json(rated_power_instant),
         );
         dry_core_battery.insert(
             "heat_storage_capacity".into(),
-            heat_storage_capacity.as_f64().into(),
+            decimal_json(heat_storage_capacity),
         );
-        dry_core_battery.insert("fan_pwr".into(), fan_pwr.as_f64().into());
+        dry_core_battery.insert("fan_pwr".into(), decimal_json(fan_pwr));
 
-        let (dry_core_min_output, dry_core_max_output): (Vec<[f64; 2]>, Vec<[f64; 2]>) = test_data
-            .iter()
-            .sorted_by(|a, b| Ord::cmp(&a.charge_level, &b.charge_level))
-            .map(|datum| {
-                let charge_level = datum.charge_level.as_f64();
+        let (dry_core_min_output, dry_core_max_output): (Vec<[JsonValue; 2]>, Vec<[JsonValue; 2]>) =
+            test_data
+                .iter()
+                .sorted_by(|a, b| Ord::cmp(&a.charge_level, &b.charge_level))
+                .map(|datum| {
+                    let charge_level = decimal_json(&datum.charge_level);
 
-                (
-                    [charge_level, datum.dry_core_min_output.as_f64()],
-                    [charge_level, datum.dry_core_max_output.as_f64()],
-                )
-            })
-            .unzip();
+                    (
+                        [
+                            charge_level.clone(),
+                            decimal_json(&datum.dry_core_min_output),
+                        ],
+                        [charge_level, decimal_json(&datum.dry_core_max_output)],
+                    )
+                })
+                .unzip();
 
         let state_of_charge_init = test_data
             .iter()
@@ -74,7 +81,7 @@ pub(crate) fn transform(
         dry_core_battery.insert("dry_core_max_output".into(), dry_core_max_output.into());
         dry_core_battery.insert(
             "state_of_charge_init".into(),
-            state_of_charge_init.as_f64().into(),
+            decimal_json(&state_of_charge_init),
         );
 
         dry_core_battery.remove(PRODUCT_REFERENCE_FIELD);
@@ -91,6 +98,7 @@ pub(crate) fn transform(
 
 #[cfg(test)]
 mod tests {
+    use crate::conflicts::ResolvedEntry;
     use crate::products::Product;
     use crate::transform::EnergySupplies;
     use crate::transform::catalogue::{mock_energy_supplies, transformed_input_matches_expected};
@@ -146,7 +154,11 @@ mod tests {
         let pcdb_dry_core_heat_battery = pcdb_heat_batteries.get(product_reference).unwrap();
 
         let result = transform(
-            dry_core_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                dry_core_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_dry_core_heat_battery,
             product_reference,
             &energy_supplies,
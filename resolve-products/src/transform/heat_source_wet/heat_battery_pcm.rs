@@ -1,14 +1,19 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{Product, Technology};
-use crate::transform::{EnergySupplies, InvalidProductCategoryError, ResolveProductsResult};
-use serde_json::{Map, Value as JsonValue, json};
+use crate::transform::{
+    EnergySupplies, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+};
+use crate::warnings::{WarningCode, Warnings};
+use serde_json::json;
 
 pub(crate) fn transform(
-    pcm_battery: &mut Map<String, JsonValue>,
+    pcm_battery: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     energy_supplies: &EnergySupplies,
+    warnings: &mut Warnings,
 ) -> ResolveProductsResult<()> {
     if let Technology::HeatBatteryPcm {
         a,
@@ -37,55 +42,57 @@ pub(crate) fn transform(
                 .ok_or_else(|| ResolvePcdbProductsError::from(fuel))?
         } else {
             // if fuel is not set on battery from PCDB, then documentation says that this is to be
-            // the same as the main heat generator, which we have assumed to always be "mains elec"                                                                                                                           |
+            // the same as the main heat generator, which we have assumed to always be "mains elec"
+            warnings.push(
+                WarningCode::HeatBatteryEnergySupplyDefaulted,
+                format!("{}/EnergySupply", pcm_battery.pointer()),
+                format!(
+                    "heat battery product {product_reference} does not give a fuel, so its energy supply was assumed to be \"mains elec\""
+                ),
+            );
             "mains elec"
         };
         pcm_battery.insert("EnergySupply".into(), energy_supply.into());
-        pcm_battery.insert("A".into(), a.as_f64().into());
-        pcm_battery.insert("B".into(), b.as_f64().into());
+        pcm_battery.insert("A".into(), decimal_json(a));
+        pcm_battery.insert("B".into(), decimal_json(b));
         pcm_battery.insert(
             "electricity_circ_pump".into(),
-            electricity_circ_pump.as_f64().into(),
+            decimal_json(electricity_circ_pump),
         );
         pcm_battery.insert(
             "electricity_standby".into(),
-            electricity_standby.as_f64().into(),
+            decimal_json(electricity_standby),
         );
         pcm_battery.insert(
             "flow_rate_l_per_min".into(),
-            flow_rate_l_per_min.as_f64().into(),
+            decimal_json(flow_rate_l_per_min),
         );
         pcm_battery.insert(
             "heat_storage_kJ_per_K_above_Phase_transition".into(),
-            heat_storage_kj_per_k_above_phase_transition.as_f64().into(),
+            decimal_json(heat_storage_kj_per_k_above_phase_transition),
         );
         pcm_battery.insert(
             "heat_storage_kJ_per_K_below_Phase_transition".into(),
-            heat_storage_kj_per_k_below_phase_transition.as_f64().into(),
+            decimal_json(heat_storage_kj_per_k_below_phase_transition),
         );
         pcm_battery.insert(
             "heat_storage_kJ_per_K_during_Phase_transition".into(),
-            heat_storage_kj_per_k_during_phase_transition
-                .as_f64()
-                .into(),
+            decimal_json(heat_storage_kj_per_k_during_phase_transition),
         );
-        pcm_battery.insert(
-            "inlet_diameter_mm".into(),
-            inlet_diameter_mm.as_f64().into(),
-        );
-        pcm_battery.insert("max_rated_losses".into(), max_rated_losses.as_f64().into());
-        pcm_battery.insert("max_temperature".into(), max_temperature.as_f64().into());
+        pcm_battery.insert("inlet_diameter_mm".into(), decimal_json(inlet_diameter_mm));
+        pcm_battery.insert("max_rated_losses".into(), decimal_json(max_rated_losses));
+        pcm_battery.insert("max_temperature".into(), decimal_json(max_temperature));
         pcm_battery.insert(
             "phase_transition_temperature_lower".into(),
-            phase_transition_temperature_lower.as_f64().into(),
+            decimal_json(phase_transition_temperature_lower),
         );
         pcm_battery.insert(
             "phase_transition_temperature_upper".into(),
-            phase_transition_temperature_upper.as_f64().into(),
+            decimal_json(phase_transition_temperature_upper),
         );
         pcm_battery.insert(
             "rated_charge_power".into(),
-            rated_charge_power.as_f64().into(),
+            decimal_json(rated_charge_power),
         );
         pcm_battery.insert(
             "simultaneous_charging_and_discharging".into(),
@@ -93,7 +100,7 @@ pub(crate) fn transform(
         );
         pcm_battery.insert(
             "velocity_in_HEX_tube_at_1_l_per_min_m_per_s".into(),
-            velocity_in_hex_tube_at_1_l_per_min_m_per_s.as_f64().into(),
+            decimal_json(velocity_in_hex_tube_at_1_l_per_min_m_per_s),
         );
         pcm_battery.remove(PRODUCT_REFERENCE_FIELD);
     } else {
@@ -107,10 +114,12 @@ pub(crate) fn transform(
 
 #[cfg(test)]
 mod tests {
+    use crate::conflicts::ResolvedEntry;
     use crate::products::Product;
     use crate::transform::EnergySupplies;
     use crate::transform::catalogue::{mock_energy_supplies, transformed_input_matches_expected};
     use crate::transform::heat_source_wet::heat_battery_pcm::transform;
+    use crate::warnings::{WarningCode, Warnings};
     use rstest::{fixture, rstest};
     use serde_json::{Map, Value as JsonValue, json};
     use std::collections::HashMap;
@@ -151,23 +160,41 @@ mod tests {
     }
 
     #[rstest]
-    #[case::pcm_with_pcdb_fuel("pcm")]
-    #[case::pcm_without_pcdb_fuel("pcm_without_pcdb_fuel")]
+    #[case::pcm_with_pcdb_fuel("pcm", &[])]
+    #[case::pcm_without_pcdb_fuel(
+        "pcm_without_pcdb_fuel",
+        &[WarningCode::HeatBatteryEnergySupplyDefaulted]
+    )]
     fn test_transform_heat_battery_pcm(
         pcdb_pcm_heat_batteries: HashMap<String, Product>,
         energy_supplies: EnergySupplies,
         #[case] product_reference: &str,
+        #[case] expected_warnings: &[WarningCode],
     ) {
+        let mut warnings = Warnings::default();
         let mut pcm_input = pcm_heat_battery_input(product_reference);
         let pcdb_pcm_heat_battery = pcdb_pcm_heat_batteries.get(product_reference).unwrap();
 
         let result = transform(
-            pcm_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                pcm_input.as_object_mut().unwrap(),
+                "/HeatSourceWet/pcm",
+                &mut Default::default(),
+            ),
             pcdb_pcm_heat_battery,
             product_reference,
             &energy_supplies,
+            &mut warnings,
         );
         assert!(result.is_ok());
+        assert_eq!(
+            warnings
+                .into_vec()
+                .iter()
+                .map(|warning| warning.code)
+                .collect::<Vec<_>>(),
+            expected_warnings
+        );
         let expected_input = expected_transformed_input(product_reference);
         transformed_input_matches_expected(&pcm_input, expected_input);
     }
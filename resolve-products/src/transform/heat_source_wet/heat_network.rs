@@ -1,10 +1,11 @@
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{Product, SubHeatNetwork, Technology};
-use crate::transform::{InvalidProductCategoryError, ResolveProductsResult};
-use serde_json::{Map, Value, json};
+use crate::transform::{InvalidProductCategoryError, ResolveProductsResult, decimal_json};
+use serde_json::{Value, json};
 
 pub fn transform(
-    heat_source_wet: &mut Map<String, Value>,
+    heat_source_wet: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     is_heat_pump_present: bool,
@@ -51,9 +52,9 @@ pub fn transform(
                 "name": format!("{} - {}", community_heat_network_name, sub_heat_network_name),
                 "is_export_capable": json!(false),
                 "factor": {
-                    "Emissions Factor kgCO2e/kWh": emissions_factor.as_f64(),
-                    "Emissions Factor kgCO2e/kWh including out-of-scope emissions": emissions_factor_including_out_of_scope.as_f64(),
-                    "Primary Energy Factor kWh/kWh delivered": primary_energy_factor.as_f64(),
+                    "Emissions Factor kgCO2e/kWh": decimal_json(emissions_factor),
+                    "Emissions Factor kgCO2e/kWh including out-of-scope emissions": decimal_json(emissions_factor_including_out_of_scope),
+                    "Primary Energy Factor kWh/kWh delivered": decimal_json(primary_energy_factor),
                 }
             }),
         );
@@ -72,7 +73,7 @@ mod tests {
     use super::*;
     use crate::transform::catalogue::transformed_input_matches_expected;
     use rstest::*;
-    use serde_json::{Value, json};
+    use serde_json::{Map, Value, json};
     use std::collections::HashMap;
 
     fn heat_network_reference_input(
@@ -115,7 +116,7 @@ mod tests {
         let pcdb_product = pcdb_heat_networks.get(heat_network_reference).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             pcdb_product,
             heat_network_reference,
             false,
@@ -138,7 +139,7 @@ mod tests {
         let pcdb_product = pcdb_heat_networks.get(heat_network_reference).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             pcdb_product,
             heat_network_reference,
             false,
@@ -1,20 +1,25 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{
-    HeatPumpBackupControlType, HeatPumpExhaustAirMixedFields, HeatPumpSourceType,
-    HeatPumpTestDatum, HeatPumpTestDatumExhaustAirMixedFields, HeatPumpTestLetter, Product,
-    ProductCatalogue, Technology, find_product_for_reference,
+    HeatPumpExhaustAirMixedFields, HeatPumpSourceType, HeatPumpTestDatum,
+    HeatPumpTestDatumExhaustAirMixedFields, HeatPumpTestLetter, Product, ProductCatalogue,
+    Technology, find_product_for_reference,
 };
-use crate::transform::{EnergySupplies, InvalidProductCategoryError, ResolveProductsResult};
+use crate::transform::{
+    EnergySupplies, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+};
+use crate::warnings::{WarningCode, Warnings};
 use rust_decimal::prelude::ToPrimitive;
-use serde_json::{Map, Value as JsonValue, json};
+use serde_json::{Value as JsonValue, json};
 
 pub async fn transform(
-    heat_pump: &mut Map<String, JsonValue>,
+    heat_pump: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     catalogue: &impl ProductCatalogue,
     energy_supplies: &EnergySupplies,
+    warnings: &mut Warnings,
 ) -> ResolveProductsResult<()> {
     if let Technology::HeatPump {
         source_type,
@@ -41,10 +46,11 @@ pub async fn transform(
         ..
     } = product.technology
     {
-        heat_pump.insert(
+        // whether there is a backup follows whichever backup control type is kept
+        let has_backup = heat_pump.insert(
             "backup_ctrl_type".into(),
             backup_control_type.to_string().into(),
-        );
+        ) != "None";
         heat_pump.insert(
             "min_temp_diff_flow_return_for_hp_to_operate".into(),
             min_temp_diff_flow_return_for_hp_to_operate.into(),
@@ -54,34 +60,34 @@ pub async fn transform(
             if let Some(minimum_modulation_rate_35) = minimum_modulation_rate_35 {
                 heat_pump.insert(
                     "min_modulation_rate_35".into(),
-                    minimum_modulation_rate_35.to_f64().into(),
+                    decimal_json(&minimum_modulation_rate_35),
                 );
             }
             if let Some(minimum_modulation_rate_55) = minimum_modulation_rate_55 {
                 heat_pump.insert(
                     "min_modulation_rate_55".into(),
-                    minimum_modulation_rate_55.to_f64().into(),
+                    decimal_json(&minimum_modulation_rate_55),
                 );
             }
         }
         heat_pump.insert("modulating_control".into(), modulating_control.into());
         heat_pump.insert(
             "power_crankcase_heater".into(),
-            power_crankcase_heater.to_f64().into(),
+            decimal_json(&power_crankcase_heater),
         );
         if let Some(power_heating_circ_pump) = power_heating_circ_pump {
             heat_pump.insert(
                 "power_heating_circ_pump".into(),
-                power_heating_circ_pump.to_f64().into(),
+                decimal_json(&power_heating_circ_pump),
             );
         }
         if let Some(power_heating_warm_air_fan) = power_heating_warm_air_fan {
             heat_pump.insert(
                 "power_heating_warm_air_fan".into(),
-                power_heating_warm_air_fan.to_f64().into(),
+                decimal_json(&power_heating_warm_air_fan),
             );
         }
-        if !matches!(backup_control_type, HeatPumpBackupControlType::None) {
+        if has_backup {
             if power_maximum_backup.is_none() && boiler_product_id.is_none() {
                 return Err(ResolvePcdbProductsError::InvalidProduct(
                     product_reference.to_string(),
@@ -91,7 +97,7 @@ pub async fn transform(
             if let Some(power_maximum_backup) = power_maximum_backup {
                 heat_pump.insert(
                     "power_max_backup".into(),
-                    power_maximum_backup.to_f64().into(),
+                    decimal_json(&power_maximum_backup),
                 );
             }
             if let Some(boiler_product_id) = boiler_product_id {
@@ -112,41 +118,37 @@ pub async fn transform(
                     ..
                 } = boiler_product.technology
                 {
-                    let boiler = heat_pump
-                        .entry("boiler")
-                        .or_insert_with(Default::default)
-                        .as_object_mut()
-                        .ok_or_else(|| {
-                            ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
-                                "Boiler JSON node within a heat pump was expected to be an object",
-                            )
-                        })?;
-                    boiler.insert("rated_power".into(), rated_power.as_f64().into());
+                    let mut boiler = heat_pump.nested("boiler").ok_or_else(|| {
+                        ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
+                            "Boiler JSON node within a heat pump was expected to be an object",
+                        )
+                    })?;
+                    boiler.insert("rated_power".into(), decimal_json(&rated_power));
                     boiler.insert(
                         "efficiency_full_load".into(),
-                        efficiency_full_load.as_f64().into(),
+                        decimal_json(&efficiency_full_load),
                     );
                     boiler.insert(
                         "efficiency_part_load".into(),
-                        efficiency_part_load.as_f64().into(),
+                        decimal_json(&efficiency_part_load),
                     );
                     boiler.insert("boiler_location".into(), json!(boiler_location));
-                    boiler.insert("modulation_load".into(), modulation_load.as_f64().into());
+                    boiler.insert("modulation_load".into(), decimal_json(&modulation_load));
                     boiler.insert(
                         "electricity_circ_pump".into(),
-                        electricity_circ_pump.as_f64().into(),
+                        decimal_json(&electricity_circ_pump),
                     );
                     boiler.insert(
                         "electricity_part_load".into(),
-                        electricity_part_load.as_f64().into(),
+                        decimal_json(&electricity_part_load),
                     );
                     boiler.insert(
                         "electricity_full_load".into(),
-                        electricity_full_load.as_f64().into(),
+                        decimal_json(&electricity_full_load),
                     );
                     boiler.insert(
                         "electricity_standby".into(),
-                        electricity_standby.as_f64().into(),
+                        decimal_json(&electricity_standby),
                     );
 
                     let energy_supply = energy_supplies
@@ -164,21 +166,21 @@ pub async fn transform(
             heat_pump.remove("boiler");
         }
 
-        heat_pump.insert("power_off".into(), power_off.to_f64().into());
+        heat_pump.insert("power_off".into(), decimal_json(&power_off));
         heat_pump.insert(
             "power_source_circ_pump".into(),
-            power_source_circ_pump.to_f64().into(),
+            decimal_json(&power_source_circ_pump),
         );
-        heat_pump.insert("power_standby".into(), power_standby.to_f64().into());
+        heat_pump.insert("power_standby".into(), decimal_json(&power_standby));
         heat_pump.insert("sink_type".into(), sink_type.to_string().into());
         heat_pump.insert("source_type".into(), source_type.to_string().into());
         heat_pump.insert(
             "temp_lower_operating_limit".into(),
-            temp_lower_operating_limit.to_f64().into(),
+            decimal_json(&temp_lower_operating_limit),
         );
         heat_pump.insert(
             "temp_return_feed_max".into(),
-            temp_return_feed_max.to_f64().into(),
+            decimal_json(&temp_return_feed_max),
         );
         if source_type == HeatPumpSourceType::ExhaustAirMixed {
             if let Some(HeatPumpExhaustAirMixedFields {
@@ -188,11 +190,11 @@ pub async fn transform(
             {
                 heat_pump.insert(
                     "eahp_mixed_min_temp".into(),
-                    eahp_mixed_min_temp.to_f64().into(),
+                    decimal_json(&eahp_mixed_min_temp),
                 );
                 heat_pump.insert(
                     "eahp_mixed_max_temp".into(),
-                    eahp_mixed_max_temp.to_f64().into(),
+                    decimal_json(&eahp_mixed_max_temp),
                 );
             } else {
                 return Err(ResolvePcdbProductsError::InvalidProduct(
@@ -201,6 +203,19 @@ pub async fn transform(
                 ));
             }
         }
+        let test_letter_e_count = test_data
+            .iter()
+            .filter(|&datum| datum.has_test_letter(HeatPumpTestLetter::E))
+            .count();
+        if test_letter_e_count > 0 {
+            warnings.push(
+                WarningCode::HeatPumpTestLetterEDropped,
+                format!("{}/test_data_EN14825", heat_pump.pointer()),
+                format!(
+                    "{test_letter_e_count} item(s) of test data with test letter E for heat pump product {product_reference} were left out, as HEM does not accept them"
+                ),
+            );
+        }
         heat_pump.insert(
             "test_data_EN14825".into(),
             JsonValue::from(
@@ -222,11 +237,11 @@ pub async fn transform(
                             } = datum;
 
                             let mut test_datum = json!({
-                                "capacity": capacity.to_f64(),
-                                "cop": coefficient_of_performance.to_f64(),
+                                "capacity": decimal_json(capacity),
+                                "cop": decimal_json(coefficient_of_performance),
                                 "design_flow_temp": design_flow_temperature.to_f64(),
-                                "temp_outlet": temperature_outlet.to_f64(),
-                                "temp_source": temperature_source.to_f64(),
+                                "temp_outlet": decimal_json(temperature_outlet),
+                                "temp_source": decimal_json(temperature_source),
                                 "temp_test": temperature_test.to_f64(),
                                 "test_letter": test_letter,
                             });
@@ -234,8 +249,8 @@ pub async fn transform(
                             if source_type == HeatPumpSourceType::ExhaustAirMixed {
                                 if let Some(HeatPumpTestDatumExhaustAirMixedFields { air_flow_rate, eahp_mixed_ext_air_ratio }) = exhaust_air_mixed_fields {
                                     test_datum.as_object_mut().unwrap().extend(json!({
-                                        "air_flow_rate": air_flow_rate.to_f64(),
-                                        "eahp_mixed_ext_air_ratio": eahp_mixed_ext_air_ratio.to_f64(),
+                                        "air_flow_rate": decimal_json(air_flow_rate),
+                                        "eahp_mixed_ext_air_ratio": decimal_json(eahp_mixed_ext_air_ratio),
                                     }).as_object().unwrap().clone());
                                 } else {
                                     return Err(
@@ -273,11 +288,12 @@ pub async fn transform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::conflicts::{ConflictPolicy, Conflicts};
     use crate::transform::catalogue::{
         FixtureBackedProductCatalogue, mock_energy_supplies, transformed_input_matches_expected,
     };
     use rstest::{fixture, rstest};
-    use serde_json::{Value, json};
+    use serde_json::{Map, Value, json};
     use std::collections::HashMap;
 
     fn heat_pump_input(product_reference: &str) -> Value {
@@ -341,11 +357,16 @@ mod tests {
         }
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                input.as_object_mut().unwrap(),
+                "/HeatSourceWet/hp",
+                &mut Default::default(),
+            ),
             pcdb_data,
             product_reference,
             &catalogue,
             &mock_energy_supplies(),
+            &mut Default::default(),
         )
         .await;
         assert!(result.is_ok(), "result: {result:?}");
@@ -353,4 +374,76 @@ mod tests {
         let expected_input = expected_heat_pump_input(product_reference);
         transformed_input_matches_expected(&input, expected_input);
     }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_transform_heat_pump_warns_of_dropped_test_letter_e_data(
+        pcdb_heat_pumps: HashMap<String, Product>,
+        catalogue: impl ProductCatalogue,
+    ) {
+        let mut input = heat_pump_input("hp");
+        let mut warnings = Warnings::default();
+
+        transform(
+            &mut ResolvedEntry::new(
+                input.as_object_mut().unwrap(),
+                "/HeatSourceWet/hp",
+                &mut Default::default(),
+            ),
+            &pcdb_heat_pumps["hp"],
+            "hp",
+            &catalogue,
+            &mock_energy_supplies(),
+            &mut warnings,
+        )
+        .await
+        .unwrap();
+
+        let warnings = warnings.into_vec();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, WarningCode::HeatPumpTestLetterEDropped);
+        assert_eq!(warnings[0].pointer, "/HeatSourceWet/hp/test_data_EN14825");
+    }
+
+    #[tokio::test]
+    #[rstest]
+    #[case(ConflictPolicy::PcdbWins, true)]
+    #[case(ConflictPolicy::UserWins, false)]
+    async fn test_transform_heat_pump_adds_backup_boiler_for_kept_backup_control_type(
+        pcdb_heat_pumps: HashMap<String, Product>,
+        catalogue: impl ProductCatalogue,
+        #[case] policy: ConflictPolicy,
+        #[case] expect_boiler: bool,
+    ) {
+        // the PCDB gives this heat pump a top-up backup boiler
+        let mut input = heat_pump_input("hp_with_boiler");
+        input["backup_ctrl_type"] = json!("None");
+        let mut conflicts = Conflicts::new(policy);
+
+        transform(
+            &mut ResolvedEntry::new(
+                input.as_object_mut().unwrap(),
+                "/HeatSourceWet/hp",
+                &mut conflicts,
+            ),
+            &pcdb_heat_pumps["hp_with_boiler"],
+            "hp_with_boiler",
+            &catalogue,
+            &mock_energy_supplies(),
+            &mut Default::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(input.get("boiler").is_some(), expect_boiler);
+        assert_eq!(
+            conflicts
+                .into_result()
+                .unwrap()
+                .iter()
+                .map(|conflict| conflict.pointer.as_str())
+                .collect::<Vec<_>>(),
+            ["/HeatSourceWet/hp/backup_ctrl_type"]
+        );
+    }
 }
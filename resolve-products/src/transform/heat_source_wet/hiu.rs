@@ -1,10 +1,10 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::products::{Product, Technology};
-use crate::transform::{InvalidProductCategoryError, TransformResult};
-use serde_json::{Map, Value};
+use crate::transform::{InvalidProductCategoryError, TransformResult, decimal_json};
 
 pub(crate) fn transform(
-    hiu: &mut Map<String, Value>,
+    hiu: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
 ) -> TransformResult {
@@ -14,8 +14,8 @@ pub(crate) fn transform(
         ..
     } = &product.technology
     {
-        hiu.insert("HIU_daily_loss".into(), hiu_daily_loss.as_f64().into());
-        hiu.insert("power_max".into(), max_power_water_55.as_f64().into());
+        hiu.insert("HIU_daily_loss".into(), decimal_json(hiu_daily_loss));
+        hiu.insert("power_max".into(), decimal_json(max_power_water_55));
 
         // now remove product reference
         hiu.remove(PRODUCT_REFERENCE_FIELD);
@@ -33,7 +33,7 @@ pub(crate) fn transform(
 mod tests {
     use super::*;
     use crate::transform::catalogue::transformed_input_matches_expected;
-    use serde_json::{from_str, json};
+    use serde_json::{Map, Value, from_str, json};
     use std::collections::HashMap;
 
     fn input(product_reference: &str) -> Value {
@@ -56,7 +56,7 @@ mod tests {
             from_str(include_str!("../fixtures/hiu_pcdb.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             hiu_pcdb.get(product_reference).unwrap(),
             product_reference,
         );
@@ -73,7 +73,7 @@ mod tests {
             from_str(include_str!("../fixtures/heat_pump_pcdb.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             pcdb_hps.get(product_reference).unwrap(),
             product_reference,
         );
@@ -6,18 +6,105 @@ mod heat_pump;
 mod hiu;
 
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{Product, ProductCatalogue};
-use crate::transform::{EnergySupplies, ResolveProductsResult, product_reference_from_json_object};
+use crate::transform::{
+    EnergySupplies, EntryErrors, ResolveProductsResult, json_pointer,
+    product_reference_from_json_object,
+};
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
+use crate::warnings::Warnings;
 use serde_json::Value as JsonValue;
 use smartstring::alias::String as SmartString;
 use std::collections::HashMap;
 
+const HEAT_PUMP_TECHNOLOGY_TYPES: &[&str] = &[
+    "AirSourceHeatPump",
+    "WaterSourceHeatPump",
+    "BoosterHeatPump",
+    "GroundSourceHeatPump",
+    "ExhaustAirMixedHeatPump",
+    "ExhaustAirMevHeatPump",
+    "ExhaustAirMvhrHeatPump",
+    "HybridHeatPump",
+];
+
+/// Resolves heat pump, boiler, heat battery, HIU and heat network products on wet heat sources.
+pub(crate) struct HeatSourceWetTransformer;
+
+impl Transformer for HeatSourceWetTransformer {
+    fn name(&self) -> &'static str {
+        "heat_source_wet"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![
+            TransformerInfo::new(
+                self.name(),
+                "/HeatSourceWet/*",
+                Some("HeatPump"),
+                HEAT_PUMP_TECHNOLOGY_TYPES,
+            ),
+            TransformerInfo::new(
+                self.name(),
+                "/HeatSourceWet/*",
+                Some("Boiler"),
+                &["RegularBoiler", "CombiBoiler"],
+            ),
+            TransformerInfo::new(
+                self.name(),
+                "/HeatSourceWet/*",
+                Some("pcm"),
+                &["HeatBatteryPCM"],
+            )
+            .with_discriminator_field("battery_type"),
+            TransformerInfo::new(
+                self.name(),
+                "/HeatSourceWet/*",
+                Some("dry_core"),
+                &["HeatBatteryDryCore"],
+            )
+            .with_discriminator_field("battery_type"),
+            TransformerInfo::new(
+                self.name(),
+                "/HeatSourceWet/*",
+                Some("HIU"),
+                &["HeatInterfaceUnit"],
+            ),
+            TransformerInfo::new(self.name(), "/HeatSourceWet/*", None, &["HeatNetworks"])
+                .with_reference_field("heat_network_reference"),
+        ]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(async move {
+            transform(
+                json,
+                context.products,
+                &context.product_catalogue,
+                context.energy_supplies,
+                context.errors,
+                context.warnings,
+                context.conflicts,
+            )
+            .await
+        })
+    }
+}
+
 pub async fn transform(
     json: &mut JsonValue,
     products: &HashMap<SmartString, Product>,
     catalogue: &impl ProductCatalogue,
     energy_supplies: &EnergySupplies,
+    errors: &mut EntryErrors,
+    warnings: &mut Warnings,
+    conflicts: &mut Conflicts,
 ) -> ResolveProductsResult<()> {
     let heat_source_wet = match json.pointer_mut("/HeatSourceWet") {
         Some(node) if node.is_object() => node.as_object_mut().unwrap(),
@@ -31,109 +118,133 @@ pub async fn transform(
             .is_some_and(|type_str| type_str == "HeatPump")
     });
 
-    for heat_source in heat_source_wet.values_mut() {
+    for (name, heat_source) in heat_source_wet.iter_mut() {
         if let JsonValue::Object(heat_source_object) = heat_source {
-            {
-                // unpack heat network data if that is applicable
+            let pointer = json_pointer(["HeatSourceWet", name]);
+            let result = transform_heat_source(
+                &mut ResolvedEntry::new(heat_source_object, pointer.as_str(), conflicts),
+                products,
+                catalogue,
+                energy_supplies,
+                is_heat_pump_present,
+                warnings,
+            )
+            .await;
+            errors.check(|| pointer, result)?;
+        }
+    }
+    Ok(())
+}
 
-                let is_heat_network = heat_source_object
-                    .get("is_heat_network")
-                    .and_then(JsonValue::as_bool)
-                    .ok_or_else(|| {
-                        ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
-                            "is_heat_network value was expected on a HeatSourceWet node",
-                        )
-                    })?;
-                if is_heat_network {
-                    let heat_network_reference = String::from(heat_source_object.get("heat_network_reference").and_then(JsonValue::as_str).ok_or_else(
-                        || ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
-                            "heat_network_reference value was expected on a HeatSourceWet node with is_heat_network=true",
-                        )
-                    )?);
-                    heat_network::transform(
-                        heat_source_object,
-                        &products[heat_network_reference.as_str()],
-                        &heat_network_reference,
-                        is_heat_pump_present,
-                    )?;
-                }
+async fn transform_heat_source(
+    heat_source_object: &mut ResolvedEntry<'_>,
+    products: &HashMap<SmartString, Product>,
+    catalogue: &impl ProductCatalogue,
+    energy_supplies: &EnergySupplies,
+    is_heat_pump_present: bool,
+    warnings: &mut Warnings,
+) -> ResolveProductsResult<()> {
+    {
+        // unpack heat network data if that is applicable
+
+        let is_heat_network = heat_source_object
+            .get("is_heat_network")
+            .and_then(JsonValue::as_bool)
+            .ok_or_else(|| {
+                ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
+                    "is_heat_network value was expected on a HeatSourceWet node",
+                )
+            })?;
+        if is_heat_network {
+            let heat_network_reference = String::from(heat_source_object.get("heat_network_reference").and_then(JsonValue::as_str).ok_or_else(
+                || ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
+                    "heat_network_reference value was expected on a HeatSourceWet node with is_heat_network=true",
+                )
+            )?);
+            heat_network::transform(
+                heat_source_object,
+                &products[heat_network_reference.as_str()],
+                &heat_network_reference,
+                is_heat_pump_present,
+            )?;
+        }
+    }
+
+    if let Some(heat_source_type) = heat_source_object.get("type").and_then(|v| v.as_str()) {
+        match heat_source_type {
+            "HeatPump" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
+                let product_reference = product_reference_from_json_object(heat_source_object)?;
+
+                heat_pump::transform(
+                    heat_source_object,
+                    &products[&product_reference],
+                    &product_reference,
+                    catalogue,
+                    energy_supplies,
+                    warnings,
+                )
+                .await?
             }
+            "Boiler" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
+                let product_reference = product_reference_from_json_object(heat_source_object)?;
 
-            if let Some(heat_source_type) = heat_source_object.get("type").and_then(|v| v.as_str())
-            {
-                match heat_source_type {
-                    "HeatPump" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
-                        let product_reference =
-                            product_reference_from_json_object(heat_source_object)?;
+                boiler::transform(
+                    heat_source_object,
+                    &products[&product_reference],
+                    &product_reference,
+                    energy_supplies,
+                )?
+            }
+            "HeatBattery" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
+                let product_reference = product_reference_from_json_object(heat_source_object)?;
 
-                        heat_pump::transform(
-                            heat_source_object,
-                            &products[&product_reference],
-                            &product_reference,
-                            catalogue,
-                            energy_supplies,
+                let battery_type = heat_source_object
+                    .get("battery_type")
+                    .and_then(|battery_type| battery_type.as_str())
+                    .ok_or_else(|| {
+                        ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
+                            "A HeatBattery heat source wet was expected to have a battery_type.",
                         )
-                        .await?
-                    }
-                    "Boiler" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
-                        let product_reference =
-                            product_reference_from_json_object(heat_source_object)?;
+                    })?;
 
-                        boiler::transform(
+                match battery_type {
+                    "pcm" => {
+                        heat_battery_pcm::transform(
                             heat_source_object,
                             &products[&product_reference],
                             &product_reference,
                             energy_supplies,
-                        )?
+                            warnings,
+                        )?;
                     }
-                    "HeatBattery" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
-                        let product_reference =
-                            product_reference_from_json_object(heat_source_object)?;
-
-                        let battery_type = heat_source_object
-                            .get("battery_type")
-                            .and_then(|battery_type| battery_type.as_str())
-                            .ok_or_else(|| {
-                                ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck("A HeatBattery heat source wet was expected to have a battery_type.")
-                            })?;
-
-                        match battery_type {
-                            "pcm" => {
-                                heat_battery_pcm::transform(
-                                    heat_source_object,
-                                    &products[&product_reference],
-                                    &product_reference,
-                                    energy_supplies,
-                                )?;
-                            }
-                            "dry_core" => heat_battery_dry_core::transform(
-                                heat_source_object,
-                                &products[&product_reference],
-                                &product_reference,
-                                energy_supplies,
-                            )?,
-                            _ => return Err(
-                                ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
-                                    "A HeatBattery heat source wet was expected to have a valid battery_type (pcm or dry_core).",
-                                ),
+                    "dry_core" => heat_battery_dry_core::transform(
+                        heat_source_object,
+                        &products[&product_reference],
+                        &product_reference,
+                        energy_supplies,
+                    )?,
+                    _ => {
+                        return Err(
+                            ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
+                                "A HeatBattery heat source wet was expected to have a valid battery_type (pcm or dry_core).",
                             ),
-                        }
+                        );
                     }
-                    "HIU" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
-                        let product_reference =
-                            product_reference_from_json_object(heat_source_object)?;
-
-                        hiu::transform(
-                            heat_source_object,
-                            &products[&product_reference],
-                            &product_reference,
-                        )?
-                    }
-                    _ => {}
                 }
             }
+            "HIU" if heat_source_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
+                let product_reference = product_reference_from_json_object(heat_source_object)?;
+
+                hiu::transform(
+                    heat_source_object,
+                    &products[&product_reference],
+                    &product_reference,
+                )?
+            }
+            _ => {}
         }
     }
+
     Ok(())
 }
 
@@ -232,6 +343,9 @@ mod tests {
             &heat_source_wet_pcdb_products,
             &dummy_catalogue,
             &energy_supplies,
+            &mut Default::default(),
+            &mut Default::default(),
+            &mut Default::default(),
         )
         .await;
         assert!(result.is_ok());
@@ -299,6 +413,9 @@ mod tests {
             &heat_source_wet_pcdb_products,
             &dummy_catalogue,
             &energy_supplies,
+            &mut Default::default(),
+            &mut Default::default(),
+            &mut Default::default(),
         )
         .await;
 
@@ -306,4 +423,39 @@ mod tests {
         let error = hp_result.unwrap_err().to_string();
         assert!(error.contains("There were mismatch errors where provided product references related to incompatible product categories"));
     }
+
+    #[tokio::test]
+    #[rstest]
+    async fn test_transform_collects_errors_for_every_heat_source(
+        heat_source_wet_pcdb_products: HashMap<SmartString, Product>,
+        dummy_catalogue: impl ProductCatalogue,
+        energy_supplies: EnergySupplies,
+    ) {
+        let mut input = incorrect_boiler_input();
+        input["HeatSourceWet"]["hp"] = incorrect_heat_pump_input()["HeatSourceWet"]["hp"].clone();
+        let mut errors = EntryErrors::collecting();
+
+        let result = transform(
+            &mut input,
+            &heat_source_wet_pcdb_products,
+            &dummy_catalogue,
+            &energy_supplies,
+            &mut errors,
+            &mut Default::default(),
+            &mut Default::default(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        let Err(ResolvePcdbProductsError::ResolutionErrors(errors)) = errors.into_result() else {
+            panic!("Expected errors to have been collected");
+        };
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| error.pointer.as_str())
+                .collect::<Vec<_>>(),
+            ["/HeatSourceWet/boiler", "/HeatSourceWet/hp"]
+        );
+    }
 }
@@ -1,17 +1,18 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
-use crate::in_use_factors::{InUseFactorsAccess, MechanicalVentilationSystemType};
+use crate::in_use_factors::{DynInUseFactorsAccess, MechanicalVentilationSystemType};
 use crate::products::{Product, Technology};
 use crate::transform::mechanical_ventilation::resolve_sfp_in_use_factor;
-use crate::transform::{InvalidProductCategoryError, ResolveProductsResult};
-use serde_json::{Map, Value as JsonValue, json};
+use crate::transform::{InvalidProductCategoryError, ResolveProductsResult, decimal_json};
+use serde_json::Value as JsonValue;
 
 pub(crate) async fn transform(
-    mech_vent: &mut Map<String, JsonValue>,
+    mech_vent: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     number_of_wetrooms: usize,
-    in_use_factors_access: &impl InUseFactorsAccess,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
 ) -> ResolveProductsResult<()> {
     if let Technology::CentralisedMev { test_data, .. } = &product.technology {
         let has_both_duct_sizes = test_data.iter().any(|datum| datum.duct_size == 1)
@@ -66,7 +67,7 @@ pub(crate) async fn transform(
         if !mech_vent.contains_key("measured_fan_power")
             || !mech_vent.contains_key("measured_air_flow_rate")
         {
-            mech_vent.insert("SFP".into(), json!(test_datum.sfp.as_f64()));
+            mech_vent.insert("SFP".into(), decimal_json(&test_datum.sfp));
         }
 
         let duct_type = &test_datum.duct_type;
@@ -78,10 +79,7 @@ pub(crate) async fn transform(
             installed_under_approved_scheme,
         )
         .await?;
-        mech_vent.insert(
-            "SFP_in_use_factor".into(),
-            json!(sfp_in_use_factor.as_f64()),
-        );
+        mech_vent.insert("SFP_in_use_factor".into(), decimal_json(&sfp_in_use_factor));
 
         mech_vent.remove("indicated_duct_size");
         mech_vent.remove("installed_under_approved_scheme");
@@ -100,6 +98,7 @@ pub(crate) async fn transform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::in_use_factors::InUseFactorsAccess;
     use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
     use crate::transform::catalogue::transformed_input_matches_expected;
     use crate::transform::mechanical_ventilation::{
@@ -147,7 +146,11 @@ mod tests {
         let pcdb_mev = pcdb_products.get("centralisedMev").unwrap();
 
         let result = transform(
-            mev_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mev_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mev,
             product_reference,
             number_of_wet_rooms,
@@ -177,7 +180,11 @@ mod tests {
         }
 
         let result = transform(
-            mev_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mev_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mev,
             product_reference,
             6,
@@ -201,7 +208,11 @@ mod tests {
         let pcdb_mev = pcdb_products.get(product_reference).unwrap();
 
         let result = transform(
-            mev_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mev_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mev,
             product_reference,
             8,
@@ -224,7 +235,11 @@ mod tests {
         let pcdb_mev = pcdb_products.get(product_reference).unwrap();
 
         let result = transform(
-            mev_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mev_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mev,
             product_reference,
             2,
@@ -255,7 +270,11 @@ mod tests {
         let pcdb_mev = pcdb_products.get(product_reference).unwrap();
 
         let result = transform(
-            mev_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mev_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mev,
             product_reference,
             2,
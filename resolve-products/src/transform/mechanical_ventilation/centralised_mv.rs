@@ -1,17 +1,18 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
-use crate::in_use_factors::{InUseFactorsAccess, MechanicalVentilationSystemType};
+use crate::in_use_factors::{DynInUseFactorsAccess, MechanicalVentilationSystemType};
 use crate::products::{Product, Technology};
 use crate::transform::mechanical_ventilation::resolve_sfp_in_use_factor;
-use crate::transform::{InvalidProductCategoryError, ResolveProductsResult};
-use serde_json::{Map, Value as JsonValue, json};
+use crate::transform::{InvalidProductCategoryError, ResolveProductsResult, decimal_json};
+use serde_json::{Value as JsonValue, json};
 
 pub(crate) async fn transform(
-    mech_vent: &mut Map<String, JsonValue>,
+    mech_vent: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     number_of_wetrooms: usize,
-    in_use_factors_access: &impl InUseFactorsAccess,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
 ) -> ResolveProductsResult<()> {
     if let Technology::CentralisedMv { test_data, .. } = &product.technology {
         let test_data_matching_number_of_wet_rooms: Vec<_> = test_data
@@ -39,7 +40,7 @@ pub(crate) async fn transform(
         if !mech_vent.contains_key("measured_fan_power")
             || !mech_vent.contains_key("measured_air_flow_rate")
         {
-            mech_vent.insert("SFP".into(), test_datum.sfp.as_f64().into());
+            mech_vent.insert("SFP".into(), decimal_json(&test_datum.sfp));
         }
 
         // Centralised MV is sent in request as a Centralised MVHR, but we need to give it a zero MVHR efficiency as it has no heat recovery
@@ -54,10 +55,7 @@ pub(crate) async fn transform(
             installed_under_approved_scheme,
         )
         .await?;
-        mech_vent.insert(
-            "SFP_in_use_factor".into(),
-            json!(sfp_in_use_factor.as_f64()),
-        );
+        mech_vent.insert("SFP_in_use_factor".into(), decimal_json(&sfp_in_use_factor));
 
         mech_vent.remove("installed_under_approved_scheme");
         mech_vent.remove(PRODUCT_REFERENCE_FIELD);
@@ -75,6 +73,7 @@ pub(crate) async fn transform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::in_use_factors::InUseFactorsAccess;
     use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
     use crate::products::Product;
     use crate::transform::catalogue::transformed_input_matches_expected;
@@ -132,7 +131,11 @@ mod tests {
         let pcdb_mv = pcdb_products.get("centralisedMv").unwrap();
 
         let result = transform(
-            mv_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mv_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mv,
             product_reference,
             number_of_wet_rooms,
@@ -162,7 +165,7 @@ mod tests {
         let pcdb_mv = pcdb_products.get("centralisedMv").unwrap();
 
         let result = transform(
-            mv_input,
+            &mut ResolvedEntry::new(mv_input, "", &mut Default::default()),
             pcdb_mv,
             product_reference,
             4,
@@ -186,7 +189,11 @@ mod tests {
         let pcdb_mv = pcdb_products.get(product_reference).unwrap();
 
         let result = transform(
-            mv_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mv_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mv,
             product_reference,
             9,
@@ -209,7 +216,11 @@ mod tests {
         });
 
         let result = transform(
-            mv_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mv_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mv,
             product_reference,
             1,
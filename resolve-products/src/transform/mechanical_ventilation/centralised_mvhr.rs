@@ -1,17 +1,18 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
-use crate::in_use_factors::{InUseFactorsAccess, MechanicalVentilationSystemType};
+use crate::in_use_factors::{DynInUseFactorsAccess, MechanicalVentilationSystemType};
 use crate::products::{Product, Technology};
 use crate::transform::mechanical_ventilation::resolve_sfp_in_use_factor;
-use crate::transform::{InvalidProductCategoryError, ResolveProductsResult};
-use serde_json::{Map, Value as JsonValue, json};
+use crate::transform::{InvalidProductCategoryError, ResolveProductsResult, decimal_json};
+use serde_json::Value as JsonValue;
 
 pub(crate) async fn transform(
-    mech_vent: &mut Map<String, JsonValue>,
+    mech_vent: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     number_of_wetrooms: usize,
-    in_use_factors_access: &impl InUseFactorsAccess,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
 ) -> ResolveProductsResult<()> {
     if let Technology::CentralisedMvhr { test_data, .. } = &product.technology {
         let test_data_matching_number_of_wet_rooms: Vec<_> = test_data
@@ -39,10 +40,10 @@ pub(crate) async fn transform(
         if !mech_vent.contains_key("measured_fan_power")
             || !mech_vent.contains_key("measured_air_flow_rate")
         {
-            mech_vent.insert("SFP".into(), test_datum.sfp.as_f64().into());
+            mech_vent.insert("SFP".into(), decimal_json(&test_datum.sfp));
         }
 
-        mech_vent.insert("mvhr_eff".into(), json!(test_datum.mvhr_eff.as_f64()));
+        mech_vent.insert("mvhr_eff".into(), decimal_json(&test_datum.mvhr_eff));
 
         let duct_type = &test_datum.duct_type;
         let installed_under_approved_scheme = mech_vent.get("installed_under_approved_scheme").and_then(JsonValue::as_bool).ok_or_else(|| { ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck("Centralised MVHR input was expected to have an 'installed_under_approved_scheme' field that is a boolean")})?;
@@ -53,10 +54,7 @@ pub(crate) async fn transform(
             installed_under_approved_scheme,
         )
         .await?;
-        mech_vent.insert(
-            "SFP_in_use_factor".into(),
-            json!(sfp_in_use_factor.as_f64()),
-        );
+        mech_vent.insert("SFP_in_use_factor".into(), decimal_json(&sfp_in_use_factor));
 
         mech_vent.remove("installed_under_approved_scheme");
         mech_vent.remove(PRODUCT_REFERENCE_FIELD);
@@ -74,6 +72,7 @@ pub(crate) async fn transform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::in_use_factors::InUseFactorsAccess;
     use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
     use crate::products::Product;
     use crate::transform::catalogue::transformed_input_matches_expected;
@@ -131,7 +130,11 @@ mod tests {
         let pcdb_mvhr = pcdb_products.get("centralisedMvhr").unwrap();
 
         let result = transform(
-            mvhr_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mvhr_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mvhr,
             product_reference,
             number_of_wet_rooms,
@@ -161,7 +164,7 @@ mod tests {
         let pcdb_mvhr = pcdb_products.get("centralisedMvhr").unwrap();
 
         let result = transform(
-            mvhr_input,
+            &mut ResolvedEntry::new(mvhr_input, "", &mut Default::default()),
             pcdb_mvhr,
             product_reference,
             4,
@@ -185,7 +188,11 @@ mod tests {
         let pcdb_mvhr = pcdb_products.get(product_reference).unwrap();
 
         let result = transform(
-            mvhr_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mvhr_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mvhr,
             product_reference,
             9,
@@ -206,7 +213,11 @@ mod tests {
         let pcdb_mvhr = pcdb_products.get(product_reference).unwrap();
 
         let result = transform(
-            mvhr_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mvhr_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mvhr,
             product_reference,
             1,
@@ -1,6 +1,7 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
-use crate::in_use_factors::InUseFactorsAccess;
+use crate::in_use_factors::DynInUseFactorsAccess;
 use crate::products::{
     DecentralisedMevInstallationConfiguration, DecentralisedMevTestDatum,
     MechanicalVentilationDuctType, Product, Technology,
@@ -8,15 +9,15 @@ use crate::products::{
 use crate::transform::mechanical_ventilation::{
     MechanicalVentilationSystemType, resolve_sfp_in_use_factor,
 };
-use crate::transform::{InvalidProductCategoryError, ResolveProductsResult};
+use crate::transform::{InvalidProductCategoryError, ResolveProductsResult, decimal_json};
 use serde::Deserialize;
-use serde_json::{Map, Value as JsonValue, json};
+use serde_json::Value as JsonValue;
 
 pub(crate) async fn transform(
-    mech_vent: &mut Map<String, JsonValue>,
+    mech_vent: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
-    in_use_factors_access: &impl InUseFactorsAccess,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
 ) -> ResolveProductsResult<()> {
     if let Technology::DecentralisedMev { test_data, .. } = &product.technology {
         let installation_type = mech_vent
@@ -65,7 +66,7 @@ pub(crate) async fn transform(
                 InstallationLocation::OtherWetRoom => test_datum.sfp2,
             };
 
-            mech_vent.insert("SFP".into(), json!(sfp.as_f64()));
+            mech_vent.insert("SFP".into(), decimal_json(&sfp));
         }
 
         {
@@ -82,10 +83,7 @@ pub(crate) async fn transform(
                 installed_under_approved_scheme,
             )
             .await?;
-            mech_vent.insert(
-                "SFP_in_use_factor".into(),
-                json!(sfp_in_use_factor.as_f64()),
-            );
+            mech_vent.insert("SFP_in_use_factor".into(), decimal_json(&sfp_in_use_factor));
         }
 
         mech_vent.remove("installation_type");
@@ -111,6 +109,7 @@ enum InstallationLocation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::in_use_factors::InUseFactorsAccess;
     use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
     use crate::transform::catalogue::transformed_input_matches_expected;
     use crate::transform::mechanical_ventilation::{
@@ -173,7 +172,11 @@ mod tests {
             .unwrap();
 
         let result = transform(
-            mev_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mev_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mev,
             product_reference,
             &in_use_factor_access,
@@ -196,7 +199,11 @@ mod tests {
         let pcdb_mev = pcdb_products.get(product_reference).unwrap();
 
         let result = transform(
-            mev_input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(
+                mev_input.as_object_mut().unwrap(),
+                "",
+                &mut Default::default(),
+            ),
             pcdb_mev,
             product_reference,
             &in_use_factor_access,
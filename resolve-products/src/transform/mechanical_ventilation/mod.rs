@@ -4,27 +4,80 @@ pub mod centralised_mvhr;
 pub mod decentralised_mev;
 
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
 use crate::errors::ResolvePcdbProductsError;
-use crate::in_use_factors::{
-    InUseFactorsAccess, MVInUseFactorEntry, MechanicalVentilationSystemType,
-};
+use crate::in_use_factors::{DynInUseFactorsAccess, MechanicalVentilationSystemType};
 use crate::products::{
     MechanicalVentilationDuctType, MechanicalVentilationInstallationType, Product, Technology,
 };
 use crate::transform::{
-    InvalidProductCategoryError, ResolveProductsResult, product_reference_from_json_object,
+    EntryErrors, InvalidProductCategoryError, ResolveProductsResult, json_pointer,
+    product_reference_from_json_object,
 };
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
 use rust_decimal::Decimal;
-#[cfg(test)]
-use serde_json::Map;
 use serde_json::Value as JsonValue;
 use smartstring::alias::String as SmartString;
 use std::collections::HashMap;
 
+/// Resolves MEV, MVHR and MV products on mechanical ventilation systems by their `vent_type`.
+pub(crate) struct MechanicalVentilationTransformer;
+
+impl Transformer for MechanicalVentilationTransformer {
+    fn name(&self) -> &'static str {
+        "mechanical_ventilation"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![
+            TransformerInfo::new(
+                self.name(),
+                "/InfiltrationVentilation/MechanicalVentilation/*",
+                Some("Decentralised continuous MEV"),
+                &["DecentralisedMev"],
+            )
+            .with_discriminator_field("vent_type"),
+            TransformerInfo::new(
+                self.name(),
+                "/InfiltrationVentilation/MechanicalVentilation/*",
+                Some("Centralised continuous MEV"),
+                &["CentralisedMev"],
+            )
+            .with_discriminator_field("vent_type"),
+            TransformerInfo::new(
+                self.name(),
+                "/InfiltrationVentilation/MechanicalVentilation/*",
+                Some("MVHR"),
+                &["CentralisedMvhr", "CentralisedMv"],
+            )
+            .with_discriminator_field("vent_type"),
+        ]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(async move {
+            transform(
+                json,
+                context.products,
+                context.in_use_factors_access,
+                context.errors,
+                context.conflicts,
+            )
+            .await
+        })
+    }
+}
+
 pub async fn transform(
     json: &mut JsonValue,
     products: &HashMap<SmartString, Product>,
-    in_use_factors_access: &impl InUseFactorsAccess,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
 ) -> ResolveProductsResult<()> {
     let number_of_wetrooms = match json.pointer_mut("/NumberOfWetRooms") {
         Some(node) if node.is_u64() => node.as_u64().unwrap(),
@@ -43,92 +96,106 @@ pub async fn transform(
             _ => return Ok(()),
         };
 
-    for mech_vent in mechanical_ventilation.values_mut() {
+    for (name, mech_vent) in mechanical_ventilation.iter_mut() {
         if let JsonValue::Object(mech_vent_object) = mech_vent {
-            if let Some(vent_type) = mech_vent_object.get("vent_type").and_then(|v| v.as_str()) {
-                match vent_type {
-                    "Decentralised continuous MEV"
-                        if mech_vent_object.contains_key(PRODUCT_REFERENCE_FIELD) =>
-                    {
-                        let product_reference =
-                            product_reference_from_json_object(mech_vent_object)?;
+            let pointer = json_pointer(["InfiltrationVentilation", "MechanicalVentilation", name]);
+            let result = transform_mech_vent(
+                &mut ResolvedEntry::new(mech_vent_object, pointer.as_str(), conflicts),
+                products,
+                number_of_wetrooms,
+                in_use_factors_access,
+            )
+            .await;
+            errors.check(|| pointer, result)?;
+        }
+    }
+    Ok(())
+}
 
-                        decentralised_mev::transform(
+async fn transform_mech_vent(
+    mech_vent_object: &mut ResolvedEntry<'_>,
+    products: &HashMap<SmartString, Product>,
+    number_of_wetrooms: u64,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
+) -> ResolveProductsResult<()> {
+    if let Some(vent_type) = mech_vent_object.get("vent_type").and_then(|v| v.as_str()) {
+        match vent_type {
+            "Decentralised continuous MEV"
+                if mech_vent_object.contains_key(PRODUCT_REFERENCE_FIELD) =>
+            {
+                let product_reference = product_reference_from_json_object(mech_vent_object)?;
+
+                decentralised_mev::transform(
+                    mech_vent_object,
+                    &products[&product_reference],
+                    &product_reference,
+                    in_use_factors_access,
+                )
+                .await?
+            }
+            "Centralised continuous MEV"
+                if mech_vent_object.contains_key(PRODUCT_REFERENCE_FIELD) =>
+            {
+                let product_reference = product_reference_from_json_object(mech_vent_object)?;
+
+                centralised_mev::transform(
+                    mech_vent_object,
+                    &products[&product_reference],
+                    &product_reference,
+                    number_of_wetrooms as usize,
+                    in_use_factors_access,
+                )
+                .await?
+            }
+            "MVHR" if mech_vent_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
+                let product_reference = product_reference_from_json_object(mech_vent_object)?;
+
+                let product = &products[&product_reference];
+
+                match &product.technology {
+                    Technology::CentralisedMvhr { .. } => {
+                        centralised_mvhr::transform(
                             mech_vent_object,
-                            &products[&product_reference],
+                            product,
                             &product_reference,
+                            number_of_wetrooms as usize,
                             in_use_factors_access,
                         )
-                        .await?
+                        .await?;
                     }
-                    "Centralised continuous MEV"
-                        if mech_vent_object.contains_key(PRODUCT_REFERENCE_FIELD) =>
-                    {
-                        let product_reference =
-                            product_reference_from_json_object(mech_vent_object)?;
-
-                        centralised_mev::transform(
+                    Technology::CentralisedMv { .. } => {
+                        centralised_mv::transform(
                             mech_vent_object,
-                            &products[&product_reference],
+                            product,
                             &product_reference,
                             number_of_wetrooms as usize,
                             in_use_factors_access,
                         )
-                        .await?
+                        .await?;
                     }
-                    "MVHR" if mech_vent_object.contains_key(PRODUCT_REFERENCE_FIELD) => {
-                        let product_reference =
-                            product_reference_from_json_object(mech_vent_object)?;
-
-                        let product = &products[&product_reference];
-
-                        match &product.technology {
-                            Technology::CentralisedMvhr { .. } => {
-                                centralised_mvhr::transform(
-                                    mech_vent_object,
-                                    product,
-                                    &product_reference,
-                                    number_of_wetrooms as usize,
-                                    in_use_factors_access,
-                                )
-                                .await?;
-                            }
-                            Technology::CentralisedMv { .. } => {
-                                centralised_mv::transform(
-                                    mech_vent_object,
-                                    product,
-                                    &product_reference,
-                                    number_of_wetrooms as usize,
-                                    in_use_factors_access,
-                                )
-                                .await?;
-                            }
-                            _ => {
-                                return Err(InvalidProductCategoryError::from((
-                                    product_reference,
-                                    "Centralised MV/MVHR (mechanical ventilation)",
-                                ))
-                                .into());
-                            }
-                        }
+                    _ => {
+                        return Err(InvalidProductCategoryError::from((
+                            product_reference,
+                            "Centralised MV/MVHR (mechanical ventilation)",
+                        ))
+                        .into());
                     }
-                    _ => {}
                 }
             }
+            _ => {}
         }
     }
+
     Ok(())
 }
 
 async fn resolve_sfp_in_use_factor(
-    in_use_factors_access: &impl InUseFactorsAccess,
+    in_use_factors_access: &dyn DynInUseFactorsAccess,
     system_type: &MechanicalVentilationSystemType,
     duct_type: &MechanicalVentilationDuctType,
     installed_under_approved_scheme: bool,
 ) -> ResolveProductsResult<Decimal> {
-    let in_use_factors = in_use_factors_access
-        .in_use_factors::<MVInUseFactorEntry>()
-        .await?;
+    let in_use_factors = in_use_factors_access.mv_in_use_factors().await?;
     let installation_type = if installed_under_approved_scheme {
         MechanicalVentilationInstallationType::InstalledUnderApprovedScheme
     } else {
@@ -152,7 +219,9 @@ fn mechanical_ventilation_pcdb_products() -> HashMap<String, Product> {
 }
 
 #[cfg(test)]
-fn expected_transformed_mech_vent_input(product_reference: &str) -> Map<String, JsonValue> {
+fn expected_transformed_mech_vent_input(
+    product_reference: &str,
+) -> serde_json::Map<String, JsonValue> {
     let expected_mechanical_ventilation: JsonValue = serde_json::from_str(include_str!(
         "../fixtures/mechanical_ventilation_input_transformed.json"
     ))
@@ -277,6 +346,8 @@ mod tests {
             &mut mechanical_ventilation_input,
             &mechanical_ventilation_pcdb_products,
             &FixtureBackedInUseFactorsAccess,
+            &mut Default::default(),
+            &mut Default::default(),
         )
         .await;
         assert!(result.is_ok());
@@ -338,6 +409,8 @@ mod tests {
                         &mut mechanical_ventilation_input_referencing_bad_category,
                         &non_mech_vent_pcdb_products,
                         &FixtureBackedInUseFactorsAccess,
+                        &mut Default::default(),
+                        &mut Default::default(),
                     )
                     .await,
                     Err(ResolvePcdbProductsError::ProductCategoryMismatches(_))
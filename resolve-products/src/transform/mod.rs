@@ -1,18 +1,43 @@
 mod air_powered_shower;
+mod electric_battery;
 mod heat_pump_hw_only;
 pub mod heat_source_wet;
 mod mechanical_ventilation;
+mod photovoltaic;
 mod smart_hot_water_tank;
+mod solar_thermal;
+mod space_cool_system;
 mod space_heat_system;
+mod storage_tank;
 mod wwhrs;
 
-use crate::errors::ResolvePcdbProductsError;
-use crate::in_use_factors::DynamoDbBackedInUseFactorsAccess;
+pub(crate) use air_powered_shower::AirPoweredShowerTransformer;
+pub(crate) use electric_battery::ElectricBatteryTransformer;
+pub(crate) use heat_pump_hw_only::HeatPumpHotWaterOnlyTransformer;
+pub(crate) use heat_source_wet::HeatSourceWetTransformer;
+pub(crate) use mechanical_ventilation::MechanicalVentilationTransformer;
+pub(crate) use photovoltaic::PhotovoltaicTransformer;
+pub(crate) use smart_hot_water_tank::SmartHotWaterTankTransformer;
+pub(crate) use solar_thermal::SolarThermalTransformer;
+pub(crate) use space_cool_system::SpaceCoolSystemTransformer;
+pub(crate) use space_heat_system::SpaceHeatSystemTransformer;
+pub(crate) use storage_tank::StorageTankTransformer;
+pub(crate) use wwhrs::WwhrsTransformer;
+
+use crate::conflicts::Conflicts;
+use crate::errors::{LocatedError, ResolvePcdbProductsError};
+use crate::in_use_factors::InUseFactorsAccess;
 use crate::products::{
-    DynamoDbBackedProductCatalogue, FuelType, Product, Technology, find_products_for_references,
+    FuelType, Product, ProductCatalogue, Technology, find_products_for_references,
+};
+use crate::report::{
+    ModuleChangeRecorder, ResolutionReport, product_reference_locations, push_pointer_token,
 };
+use crate::transformer::{DocumentContext, TransformerRegistry};
+use crate::versions::VersionBehaviour;
+use crate::warnings::Warnings;
 use crate::{PRODUCT_REFERENCE_FIELD, extract_product_references};
-use aws_sdk_dynamodb::client::Client as DynamoDbClient;
+use rust_decimal::Decimal;
 use serde_json::Map;
 use serde_json::value::Value as JsonValue;
 use smartstring::alias::String;
@@ -20,15 +45,22 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use thiserror::Error;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn transform_json(
     json: &mut JsonValue,
-    dynamo_client: &DynamoDbClient,
+    product_catalogue: &impl ProductCatalogue,
+    in_use_factors_access: &impl InUseFactorsAccess,
+    mut report: Option<&mut ResolutionReport>,
+    mut errors: EntryErrors,
+    warnings: &mut Warnings,
+    conflicts: &mut Conflicts,
+    transformers: &TransformerRegistry,
+    version_behaviour: &VersionBehaviour,
 ) -> ResolveProductsResult<()> {
     let product_references = extract_product_references(json)?;
-    let product_catalogue = DynamoDbBackedProductCatalogue::new(dynamo_client);
     let products: HashMap<String, Product> =
-        find_products_for_references(&product_references, &product_catalogue).await?;
-    check_unknown_categories(&products)?;
+        find_products_for_references(&product_references, product_catalogue).await?;
+    check_unknown_categories(json, &products, transformers, &mut errors)?;
 
     let energy_supplies = extract_energy_supplies(json).map_err(|_| {
         ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
@@ -36,20 +68,31 @@ pub async fn transform_json(
         )
     })?;
 
-    let in_use_factors_access = DynamoDbBackedInUseFactorsAccess::new(dynamo_client);
+    if let Some(report) = report.as_deref_mut() {
+        report.record_product_references(json, &products);
+    }
+    let mut recorder = ModuleChangeRecorder::new(report, json);
+    let mut context = DocumentContext {
+        products: &products,
+        product_catalogue,
+        in_use_factors_access,
+        energy_supplies: &energy_supplies,
+        version_behaviour,
+        errors: &mut errors,
+        warnings,
+        conflicts,
+    };
 
-    heat_source_wet::transform(json, &products, &product_catalogue, &energy_supplies).await?;
-    space_heat_system::transform(json, &products, &energy_supplies)?;
-    wwhrs::transform(json, &products)?;
-    heat_pump_hw_only::transform(json, &products, &in_use_factors_access, &energy_supplies).await?;
-    smart_hot_water_tank::transform(json, &products)?;
-    mechanical_ventilation::transform(json, &products, &in_use_factors_access).await?;
-    air_powered_shower::transform(json, &products)?;
+    for transformer in transformers.iter() {
+        transformer.transform_document(json, &mut context).await?;
+        recorder.module_finished(transformer.name(), json);
+    }
 
-    Ok(())
+    errors.into_result()?;
+    check_all_references_consumed(json)
 }
 
-fn product_reference_from_json_object(
+pub(crate) fn product_reference_from_json_object(
     product_json: &Map<std::string::String, JsonValue>,
 ) -> Result<String, ResolvePcdbProductsError> {
     Ok(String::from(
@@ -64,21 +107,121 @@ fn product_reference_from_json_object(
 }
 
 fn check_unknown_categories(
+    json: &JsonValue,
     products: &HashMap<String, Product>,
+    transformers: &TransformerRegistry,
+    errors: &mut EntryErrors,
 ) -> Result<(), ResolvePcdbProductsError> {
-    for (product_reference, product) in products {
-        if let Technology::Unknown = &product.technology {
-            return Err(ResolvePcdbProductsError::UnsupportedProductCategory {
-                category: "(unknown category)".to_string(),
-                product_reference: product_reference.to_string(),
-            });
+    for (pointer, _, product_reference) in product_reference_locations(json) {
+        if products.get(product_reference).is_some_and(|product| {
+            matches!(product.technology, Technology::Unknown)
+                && !transformers.has_custom_transformer_for(product.technology_type())
+        }) {
+            errors.check(
+                || pointer,
+                Err(ResolvePcdbProductsError::UnsupportedProductCategory {
+                    category: "(unknown category)".to_string(),
+                    product_reference: product_reference.to_string(),
+                }),
+            )?;
         }
     }
     Ok(())
 }
 
+/// Checks that every product reference in the document was consumed by one of the transform modules,
+/// as a reference at a location that no module handles would otherwise silently be left in place.
+fn check_all_references_consumed(json: &JsonValue) -> Result<(), ResolvePcdbProductsError> {
+    let unconsumed: Vec<_> = product_reference_locations(json)
+        .into_iter()
+        .map(|(mut pointer, field, _)| {
+            push_pointer_token(&mut pointer, field);
+            pointer
+        })
+        .collect();
+
+    if unconsumed.is_empty() {
+        Ok(())
+    } else {
+        Err(ResolvePcdbProductsError::UnconsumedProductReferences(
+            unconsumed,
+        ))
+    }
+}
+
 pub type ResolveProductsResult<T> = Result<T, ResolvePcdbProductsError>;
 
+/// Decides what happens to an error encountered while transforming one entry of the document:
+/// either it is returned straight away, or it is recorded against the entry's JSON pointer so that
+/// the rest of the document can still be transformed and all errors reported together.
+#[derive(Debug, Default)]
+pub(crate) struct EntryErrors {
+    collect: bool,
+    errors: Vec<LocatedError>,
+}
+
+impl EntryErrors {
+    pub(crate) fn collecting() -> Self {
+        Self {
+            collect: true,
+            errors: vec![],
+        }
+    }
+
+    pub(crate) fn check(
+        &mut self,
+        pointer: impl FnOnce() -> std::string::String,
+        result: ResolveProductsResult<()>,
+    ) -> ResolveProductsResult<()> {
+        match result {
+            Err(error) if self.collect && error.is_entry_error() => {
+                self.errors.push(LocatedError {
+                    pointer: pointer(),
+                    error,
+                });
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    pub(crate) fn into_result(self) -> ResolveProductsResult<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ResolvePcdbProductsError::ResolutionErrors(self.errors))
+        }
+    }
+}
+
+/// Converts a decimal from the PCDB into a JSON number.
+///
+/// With the `exact-decimals` feature, the number is written exactly as the PCDB gives it; otherwise
+/// it is the nearest `f64`, which may not be.
+pub(crate) fn decimal_json(value: &Decimal) -> JsonValue {
+    #[cfg(feature = "exact-decimals")]
+    {
+        JsonValue::Number(
+            serde_json::from_str(&value.to_string())
+                .expect("A decimal should always be writable as a JSON number"),
+        )
+    }
+    #[cfg(not(feature = "exact-decimals"))]
+    {
+        value.as_f64().into()
+    }
+}
+
+/// Builds a JSON pointer from the given reference tokens.
+pub(crate) fn json_pointer<'a>(tokens: impl IntoIterator<Item = &'a str>) -> std::string::String {
+    let mut pointer = std::string::String::new();
+    for token in tokens {
+        push_pointer_token(&mut pointer, token);
+    }
+
+    pointer
+}
+
 pub(crate) type EnergySupplies = HashMap<FuelType, Arc<str>>;
 
 fn extract_energy_supplies(json: &JsonValue) -> Result<EnergySupplies, ()> {
@@ -129,7 +272,7 @@ impl From<InvalidProductCategoryError> for ResolvePcdbProductsError {
 }
 
 #[cfg(test)]
-mod catalogue {
+pub(crate) mod catalogue {
     use crate::errors::ResolvePcdbProductsError;
     use crate::products::{Product, ProductCatalogue};
     use crate::transform::{EnergySupplies, ResolveProductsResult, extract_energy_supplies};
@@ -211,7 +354,60 @@ mod catalogue {
         assert_eq!(actual_keys, expected_keys);
 
         for key in expected_keys {
-            assert_eq!(transformed_input[key], expected_input[key], "{:?}", key);
+            assert_eq!(
+                numbers_as_floats(&transformed_input[key]),
+                numbers_as_floats(&expected_input[key]),
+                "{:?}",
+                key
+            );
         }
     }
+
+    /// Asserts that two documents are equal, comparing numbers by value so that fixtures hold the
+    /// same whether or not PCDB decimals are written exactly (where `10` and `10.0` differ).
+    pub(crate) fn assert_json_eq(actual: &Value, expected: &Value) {
+        assert_eq!(
+            numbers_as_floats(actual),
+            numbers_as_floats(expected),
+            "actual: {}\nexpected: {}",
+            serde_json::to_string_pretty(actual).unwrap(),
+            serde_json::to_string_pretty(expected).unwrap()
+        );
+    }
+
+    fn numbers_as_floats(value: &Value) -> Value {
+        match value {
+            Value::Number(number) => number.as_f64().into(),
+            Value::Array(values) => values.iter().map(numbers_as_floats).collect(),
+            Value::Object(object) => object
+                .iter()
+                .map(|(key, value)| (key.clone(), numbers_as_floats(value)))
+                .collect(),
+            value => value.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    #[cfg(not(feature = "exact-decimals"))]
+    fn test_decimals_are_written_as_nearest_float() {
+        let decimal = Decimal::from_str("0.12345678901234567890").unwrap();
+
+        assert_eq!(decimal_json(&decimal).to_string(), "0.12345678901234568");
+        assert_eq!(decimal_json(&Decimal::from(10)).to_string(), "10.0");
+    }
+
+    #[test]
+    #[cfg(feature = "exact-decimals")]
+    fn test_decimals_are_written_exactly() {
+        let decimal = Decimal::from_str("0.12345678901234567890").unwrap();
+
+        assert_eq!(decimal_json(&decimal).to_string(), "0.12345678901234567890");
+        assert_eq!(decimal_json(&Decimal::from(10)).to_string(), "10");
+    }
 }
@@ -0,0 +1,208 @@
+use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
+use crate::errors::ResolvePcdbProductsError;
+use crate::products::{Product, Technology};
+use crate::transform::{
+    EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json, json_pointer,
+    product_reference_from_json_object,
+};
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
+use rust_decimal::Decimal;
+use serde_json::{Value as JsonValue, json};
+use smartstring::alias::String;
+use std::collections::HashMap;
+
+const INVERTER_PRODUCT_REFERENCE_FIELD: &str = "inverter_product_reference";
+
+/// Resolves the module and inverter products of PV systems.
+pub(crate) struct PhotovoltaicTransformer;
+
+impl Transformer for PhotovoltaicTransformer {
+    fn name(&self) -> &'static str {
+        "photovoltaic"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![
+            TransformerInfo::new(
+                self.name(),
+                "/OnSiteGeneration/*",
+                Some("PhotovoltaicSystem"),
+                &["PhotovoltaicModule"],
+            ),
+            TransformerInfo::new(
+                self.name(),
+                "/OnSiteGeneration/*",
+                Some("PhotovoltaicSystem"),
+                &["PhotovoltaicInverter"],
+            )
+            .with_reference_field(INVERTER_PRODUCT_REFERENCE_FIELD),
+        ]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(
+            async move { transform(json, context.products, context.errors, context.conflicts) },
+        )
+    }
+}
+
+pub(crate) fn transform(
+    json: &mut JsonValue,
+    products: &HashMap<String, Product>,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
+) -> ResolveProductsResult<()> {
+    let on_site_generation = match json.pointer_mut("/OnSiteGeneration") {
+        Some(node) if node.is_object() => node.as_object_mut().unwrap(),
+        _ => return Ok(()),
+    };
+
+    for (name, value) in on_site_generation.iter_mut() {
+        if let JsonValue::Object(system) = value {
+            let pointer = json_pointer(["OnSiteGeneration", name]);
+            let result = transform_photovoltaic_system(
+                &mut ResolvedEntry::new(system, pointer.as_str(), conflicts),
+                products,
+            );
+            errors.check(|| pointer, result)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn transform_photovoltaic_system(
+    system: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+) -> ResolveProductsResult<()> {
+    if system.get("type").and_then(JsonValue::as_str) != Some("PhotovoltaicSystem")
+        || !system.contains_key(PRODUCT_REFERENCE_FIELD)
+    {
+        return Ok(());
+    }
+
+    let module_reference = product_reference_from_json_object(system)?;
+    let Technology::PhotovoltaicModule { peak_power } = &products[&module_reference].technology
+    else {
+        return Err(
+            InvalidProductCategoryError::from((module_reference, "photovoltaic module")).into(),
+        );
+    };
+
+    let inverter_reference = system
+        .get(INVERTER_PRODUCT_REFERENCE_FIELD)
+        .and_then(JsonValue::as_str)
+        .ok_or(ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
+            "inverter_product_reference was expected as a string on a PV system with a product reference",
+        ))?;
+    let Technology::PhotovoltaicInverter {
+        rated_power_ac,
+        inverter_type,
+    } = &products[inverter_reference].technology
+    else {
+        return Err(InvalidProductCategoryError::from((
+            String::from(inverter_reference),
+            "photovoltaic inverter",
+        ))
+        .into());
+    };
+
+    let number_of_modules = system
+        .get("number_of_modules")
+        .and_then(JsonValue::as_u64)
+        .ok_or(ResolvePcdbProductsError::InvalidRequestEncounteredAfterSchemaCheck(
+            "number_of_modules was expected as a positive integer on a PV system with a product reference",
+        ))?;
+
+    system.insert(
+        "peak_power".into(),
+        decimal_json(&(peak_power * Decimal::from(number_of_modules))),
+    );
+    system.insert(
+        "inverter_peak_power_ac".into(),
+        decimal_json(rated_power_ac),
+    );
+    system.insert("inverter_type".into(), json!(inverter_type.to_string()));
+
+    system.remove(PRODUCT_REFERENCE_FIELD);
+    system.remove(INVERTER_PRODUCT_REFERENCE_FIELD);
+    system.remove("number_of_modules");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::catalogue::assert_json_eq;
+    use serde_json::from_str;
+
+    fn input(module_reference: &str, inverter_reference: &str) -> JsonValue {
+        json!({
+            "OnSiteGeneration": {
+                "PV 1": {
+                    "type": "PhotovoltaicSystem",
+                    "product_reference": module_reference,
+                    "inverter_product_reference": inverter_reference,
+                    "number_of_modules": 10,
+                    "inverter_peak_power_dc": 3.75,
+                    "ventilation_strategy": "moderately_ventilated",
+                    "pitch": 30,
+                    "orientation360": 180,
+                    "base_height": 1,
+                    "height": 1,
+                    "width": 6,
+                    "EnergySupply": "mains elec",
+                    "shading": [],
+                    "inverter_is_inside": false
+                }
+            }
+        })
+    }
+
+    fn pcdb_products() -> HashMap<String, Product> {
+        from_str(include_str!("fixtures/photovoltaic_pcdb.json")).unwrap()
+    }
+
+    #[test]
+    fn test_transform_photovoltaic_system() {
+        let mut input = input("pv_module", "pv_inverter");
+        let expected: JsonValue =
+            from_str(include_str!("fixtures/photovoltaic_transformed.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &pcdb_products(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert_json_eq(&input, &expected);
+    }
+
+    #[test]
+    fn test_transform_photovoltaic_system_errors_when_references_are_swapped() {
+        let mut input = input("pv_inverter", "pv_module");
+
+        let result = transform(
+            &mut input,
+            &pcdb_products(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("photovoltaic module")
+        );
+    }
+}
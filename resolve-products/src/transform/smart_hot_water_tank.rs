@@ -1,22 +1,67 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{Product, Technology};
 use crate::transform::{
-    InvalidProductCategoryError, ResolveProductsResult, product_reference_from_json_object,
+    EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json, json_pointer,
+    product_reference_from_json_object,
 };
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
 use serde_json::Value as JsonValue;
 use smartstring::alias::String;
 use std::collections::HashMap;
 
+/// Resolves smart hot water tank products on the hot water cylinder.
+pub(crate) struct SmartHotWaterTankTransformer;
+
+impl Transformer for SmartHotWaterTankTransformer {
+    fn name(&self) -> &'static str {
+        "smart_hot_water_tank"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![TransformerInfo::new(
+            self.name(),
+            "/HotWaterSource/hw cylinder",
+            Some("SmartHotWaterTank"),
+            &["SmartHotWaterTank"],
+        )]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(
+            async move { transform(json, context.products, context.errors, context.conflicts) },
+        )
+    }
+}
+
 pub fn transform(
     json: &mut JsonValue,
     products: &HashMap<String, Product>,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
 ) -> ResolveProductsResult<()> {
     let hot_water_source = match json.pointer_mut("/HotWaterSource/hw cylinder") {
         Some(node) if node.is_object() => node.as_object_mut().unwrap(),
         _ => return Ok(()),
     };
 
+    let pointer = json_pointer(["HotWaterSource", "hw cylinder"]);
+    let result = transform_hot_water_source(
+        &mut ResolvedEntry::new(hot_water_source, pointer.as_str(), conflicts),
+        products,
+    );
+    errors.check(|| pointer, result)
+}
+
+fn transform_hot_water_source(
+    hot_water_source: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+) -> ResolveProductsResult<()> {
     if let Some(source_type) = hot_water_source.get("type").and_then(|v| v.as_str()) {
         if matches!(source_type, "SmartHotWaterTank")
             && hot_water_source.contains_key(PRODUCT_REFERENCE_FIELD)
@@ -47,17 +92,17 @@ pub fn transform(
                     )?;
                     hot_water_source.insert(
                         "heat_exchanger_surface_area".into(),
-                        heat_exchanger_surface_area.as_f64().into(),
+                        decimal_json(&heat_exchanger_surface_area),
                     );
                 }
                 hot_water_source.insert(
                     "max_flow_rate_pump_l_per_min".into(),
-                    max_flow_rate_pump_l_per_min.as_f64().into(),
+                    decimal_json(max_flow_rate_pump_l_per_min),
                 );
-                hot_water_source.insert("power_pump_kW".into(), power_pump_kw.as_f64().into());
-                hot_water_source.insert("temp_usable".into(), temp_usable.as_f64().into());
-                hot_water_source.insert("daily_losses".into(), daily_losses.as_f64().into());
-                hot_water_source.insert("volume".into(), volume.as_f64().into());
+                hot_water_source.insert("power_pump_kW".into(), decimal_json(power_pump_kw));
+                hot_water_source.insert("temp_usable".into(), decimal_json(temp_usable));
+                hot_water_source.insert("daily_losses".into(), decimal_json(daily_losses));
+                hot_water_source.insert("volume".into(), decimal_json(volume));
 
                 // now remove product reference
                 hot_water_source.remove(PRODUCT_REFERENCE_FIELD);
@@ -78,6 +123,7 @@ pub fn transform(
 mod tests {
     use super::*;
     use crate::products::Product;
+    use crate::transform::catalogue::assert_json_eq;
     use serde_json::{from_str, json};
     use std::collections::HashMap;
 
@@ -112,16 +158,12 @@ mod tests {
         let result = transform(
             &mut input,
             &HashMap::from([(product_reference.into(), pcdb_smart_tank)]),
+            &mut Default::default(),
+            &mut Default::default(),
         );
 
         assert!(result.is_ok());
-        assert_eq!(
-            input,
-            expected,
-            "actual: {}\nexpected: {}",
-            serde_json::to_string_pretty(&input).unwrap(),
-            serde_json::to_string_pretty(&expected).unwrap()
-        );
+        assert_json_eq(&input, &expected);
     }
 
     #[test]
@@ -131,7 +173,12 @@ mod tests {
         let pcdb_hps: HashMap<String, Product> =
             from_str(include_str!("fixtures/heat_pump_pcdb.json")).unwrap();
 
-        let result = transform(&mut input, &pcdb_hps);
+        let result = transform(
+            &mut input,
+            &pcdb_hps,
+            &mut Default::default(),
+            &mut Default::default(),
+        );
 
         assert!(result.is_err());
         assert!(
@@ -160,6 +207,8 @@ mod tests {
         let result = transform(
             &mut input,
             &HashMap::from([(product_reference.into(), pcdb_smart_tank)]),
+            &mut Default::default(),
+            &mut Default::default(),
         );
 
         assert!(result.is_err());
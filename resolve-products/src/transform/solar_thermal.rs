@@ -0,0 +1,264 @@
+use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
+use crate::errors::ResolvePcdbProductsError;
+use crate::products::{FuelType, Product, Technology};
+use crate::transform::{
+    EnergySupplies, EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+    json_pointer, product_reference_from_json_object,
+};
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
+use serde_json::{Value as JsonValue, json};
+use smartstring::alias::String;
+use std::collections::HashMap;
+
+/// The sections of the document holding tanks that solar thermal systems can heat.
+const TANK_SECTIONS: [&str; 2] = ["HotWaterSource", "PreHeatedWaterSource"];
+
+/// Resolves solar thermal collector products on the heat sources of hot water and pre-heated water tanks.
+pub(crate) struct SolarThermalTransformer;
+
+impl Transformer for SolarThermalTransformer {
+    fn name(&self) -> &'static str {
+        "solar_thermal"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![
+            TransformerInfo::new(
+                self.name(),
+                "/HotWaterSource/*/HeatSource/*",
+                Some("SolarThermalSystem"),
+                &["SolarThermalCollector"],
+            ),
+            TransformerInfo::new(
+                self.name(),
+                "/PreHeatedWaterSource/*/HeatSource/*",
+                Some("SolarThermalSystem"),
+                &["SolarThermalCollector"],
+            ),
+        ]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(async move {
+            transform(
+                json,
+                context.products,
+                context.energy_supplies,
+                context.errors,
+                context.conflicts,
+            )
+        })
+    }
+}
+
+pub(crate) fn transform(
+    json: &mut JsonValue,
+    products: &HashMap<String, Product>,
+    energy_supplies: &EnergySupplies,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
+) -> ResolveProductsResult<()> {
+    for section in TANK_SECTIONS {
+        let tanks = match json.get_mut(section) {
+            Some(JsonValue::Object(tanks)) => tanks,
+            _ => continue,
+        };
+
+        for (tank_name, tank) in tanks.iter_mut() {
+            let Some(JsonValue::Object(heat_sources)) = tank.get_mut("HeatSource") else {
+                continue;
+            };
+
+            for (name, value) in heat_sources.iter_mut() {
+                if let JsonValue::Object(heat_source) = value {
+                    let pointer = json_pointer([section, tank_name, "HeatSource", name]);
+                    let result = transform_heat_source(
+                        &mut ResolvedEntry::new(heat_source, pointer.as_str(), conflicts),
+                        products,
+                        energy_supplies,
+                    );
+                    errors.check(|| pointer, result)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn transform_heat_source(
+    heat_source: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+    energy_supplies: &EnergySupplies,
+) -> ResolveProductsResult<()> {
+    if heat_source.get("type").and_then(JsonValue::as_str) != Some("SolarThermalSystem")
+        || !heat_source.contains_key(PRODUCT_REFERENCE_FIELD)
+    {
+        return Ok(());
+    }
+
+    let product_reference = product_reference_from_json_object(heat_source)?;
+    let product = &products[&product_reference];
+
+    if let Technology::SolarThermalCollector {
+        aperture_area,
+        zero_loss_efficiency,
+        first_order_heat_loss_coefficient,
+        second_order_heat_loss_coefficient,
+        incidence_angle_modifier,
+        collector_loop_pump_power,
+        collector_loop_pump_control_power,
+    } = &product.technology
+    {
+        // the collector loop pump runs on electricity
+        let energy_supply = energy_supplies
+            .get(&FuelType::Electricity)
+            .ok_or_else(|| ResolvePcdbProductsError::from(&FuelType::Electricity))?;
+        heat_source.insert("EnergySupply".into(), json!(energy_supply.as_ref()));
+        heat_source.insert("area_module".into(), decimal_json(aperture_area));
+        heat_source.insert(
+            "peak_collector_efficiency".into(),
+            decimal_json(zero_loss_efficiency),
+        );
+        heat_source.insert(
+            "first_order_hlc".into(),
+            decimal_json(first_order_heat_loss_coefficient),
+        );
+        heat_source.insert(
+            "second_order_hlc".into(),
+            decimal_json(second_order_heat_loss_coefficient),
+        );
+        heat_source.insert(
+            "incidence_angle_modifier".into(),
+            decimal_json(incidence_angle_modifier),
+        );
+        heat_source.insert("power_pump".into(), decimal_json(collector_loop_pump_power));
+        heat_source.insert(
+            "power_pump_control".into(),
+            decimal_json(collector_loop_pump_control_power),
+        );
+
+        // now remove product reference
+        heat_source.remove(PRODUCT_REFERENCE_FIELD);
+    } else {
+        return Err(InvalidProductCategoryError::from((
+            product_reference,
+            "solar thermal collector",
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::catalogue::{assert_json_eq, mock_energy_supplies};
+    use rstest::*;
+    use serde_json::from_str;
+
+    fn solar_thermal_system(product_reference: &str) -> JsonValue {
+        json!({
+            "type": "SolarThermalSystem",
+            "product_reference": product_reference,
+            "heater_position": 0.1,
+            "thermostat_position": 0.33,
+            "sol_loc": "HS",
+            "modules": 2,
+            "collector_mass_flow_rate": 0.035,
+            "tilt": 30,
+            "orientation360": 180,
+            "solar_loop_piping_hlc": 0.5
+        })
+    }
+
+    fn input(section: &str, tank: &str, product_reference: &str) -> JsonValue {
+        json!({
+            section: {
+                tank: {
+                    "type": "StorageTank",
+                    "volume": 210,
+                    "daily_losses": 1.46,
+                    "ColdWaterSource": "mains water",
+                    "HeatSource": {"solar": solar_thermal_system(product_reference)}
+                }
+            }
+        })
+    }
+
+    #[rstest]
+    #[case("HotWaterSource", "hw cylinder")]
+    #[case("PreHeatedWaterSource", "preheated tank")]
+    fn test_transform_solar_thermal_system(#[case] section: &str, #[case] tank: &str) {
+        let product_reference = "solar_collector";
+        let mut input = input(section, tank, product_reference);
+        let expected: JsonValue =
+            from_str(include_str!("fixtures/solar_thermal_transformed.json")).unwrap();
+        let pcdb_collector: Product =
+            from_str(include_str!("fixtures/solar_thermal_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &HashMap::from([(product_reference.into(), pcdb_collector)]),
+            &mock_energy_supplies(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert_json_eq(&input[section][tank]["HeatSource"]["solar"], &expected);
+    }
+
+    #[test]
+    fn test_transform_solar_thermal_system_errors_without_electricity_supply() {
+        let product_reference = "solar_collector";
+        let mut input = input("HotWaterSource", "hw cylinder", product_reference);
+        let pcdb_collector: Product =
+            from_str(include_str!("fixtures/solar_thermal_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &HashMap::from([(product_reference.into(), pcdb_collector)]),
+            &Default::default(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::NoEnergySupplyProvidedForFuelType(
+                FuelType::Electricity
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_transform_solar_thermal_system_errors_when_product_type_mismatch() {
+        let product_reference = "hp";
+        let mut input = input("HotWaterSource", "hw cylinder", product_reference);
+        let pcdb_hps: HashMap<String, Product> =
+            from_str(include_str!("fixtures/heat_pump_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &pcdb_hps,
+            &mock_energy_supplies(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("solar thermal collector")
+        );
+    }
+}
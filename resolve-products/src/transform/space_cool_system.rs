@@ -0,0 +1,174 @@
+use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
+use crate::errors::ResolvePcdbProductsError;
+use crate::products::{Product, Technology};
+use crate::transform::{
+    EnergySupplies, EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+    json_pointer, product_reference_from_json_object,
+};
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
+use serde_json::{Value as JsonValue, json};
+use smartstring::alias::String;
+use std::collections::HashMap;
+
+/// Resolves air conditioning products on space cooling systems.
+pub(crate) struct SpaceCoolSystemTransformer;
+
+impl Transformer for SpaceCoolSystemTransformer {
+    fn name(&self) -> &'static str {
+        "space_cool_system"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![TransformerInfo::new(
+            self.name(),
+            "/SpaceCoolSystem/*",
+            Some("AirConditioning"),
+            &["SplitAirConditioning", "PackagedAirConditioning"],
+        )]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(async move {
+            transform(
+                json,
+                context.products,
+                context.energy_supplies,
+                context.errors,
+                context.conflicts,
+            )
+        })
+    }
+}
+
+pub(crate) fn transform(
+    json: &mut JsonValue,
+    products: &HashMap<String, Product>,
+    energy_supplies: &EnergySupplies,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
+) -> ResolveProductsResult<()> {
+    let space_cool_systems = match json.pointer_mut("/SpaceCoolSystem") {
+        Some(node) if node.is_object() => node.as_object_mut().unwrap(),
+        _ => return Ok(()),
+    };
+
+    for (name, value) in space_cool_systems.iter_mut() {
+        if let JsonValue::Object(system) = value {
+            let pointer = json_pointer(["SpaceCoolSystem", name]);
+            let result = transform_space_cool_system(
+                &mut ResolvedEntry::new(system, pointer.as_str(), conflicts),
+                products,
+                energy_supplies,
+            );
+            errors.check(|| pointer, result)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn transform_space_cool_system(
+    system: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+    energy_supplies: &EnergySupplies,
+) -> ResolveProductsResult<()> {
+    if system.get("type").and_then(JsonValue::as_str) != Some("AirConditioning")
+        || !system.contains_key(PRODUCT_REFERENCE_FIELD)
+    {
+        return Ok(());
+    }
+
+    let product_reference = product_reference_from_json_object(system)?;
+    let product = &products[&product_reference];
+
+    if let Technology::AirConditioning {
+        fuel,
+        cooling_capacity,
+        seer,
+        frac_convective,
+        ..
+    } = &product.technology
+    {
+        let energy_supply = energy_supplies
+            .get(fuel)
+            .ok_or_else(|| ResolvePcdbProductsError::from(fuel))?;
+        system.insert("EnergySupply".into(), json!(energy_supply.as_ref()));
+        system.insert("cooling_capacity".into(), decimal_json(cooling_capacity));
+        // HEM takes the seasonal ratio as the efficiency of an air conditioning system
+        system.insert("efficiency".into(), decimal_json(seer));
+        system.insert("frac_convective".into(), decimal_json(frac_convective));
+
+        // now remove product reference
+        system.remove(PRODUCT_REFERENCE_FIELD);
+    } else {
+        return Err(
+            InvalidProductCategoryError::from((product_reference, "air conditioning")).into(),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::catalogue::{assert_json_eq, mock_energy_supplies};
+    use serde_json::from_str;
+
+    fn input(product_reference: &str) -> JsonValue {
+        json!({
+            "SpaceCoolSystem": {
+                "main": {
+                    "type": "AirConditioning",
+                    "product_reference": product_reference,
+                    "temp_setback": 30
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_transform_air_conditioning() {
+        let product_reference = "split_ac";
+        let mut input = input(product_reference);
+        let expected: JsonValue =
+            from_str(include_str!("fixtures/air_conditioning_transformed.json")).unwrap();
+        let pcdb_air_conditioning: Product =
+            from_str(include_str!("fixtures/air_conditioning_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &HashMap::from([(product_reference.into(), pcdb_air_conditioning)]),
+            &mock_energy_supplies(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert_json_eq(&input, &expected);
+    }
+
+    #[test]
+    fn test_transform_air_conditioning_errors_when_product_type_mismatch() {
+        let product_reference = "hp";
+        let mut input = input(product_reference);
+        let pcdb_hps: HashMap<String, Product> =
+            from_str(include_str!("fixtures/heat_pump_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &pcdb_hps,
+            &mock_energy_supplies(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("air conditioning"));
+    }
+}
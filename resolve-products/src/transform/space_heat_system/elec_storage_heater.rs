@@ -1,12 +1,14 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{Product, Technology};
-use crate::transform::{EnergySupplies, InvalidProductCategoryError, ResolveProductsResult};
-use rust_decimal::prelude::ToPrimitive;
-use serde_json::{Map, Value as JsonValue, json};
+use crate::transform::{
+    EnergySupplies, InvalidProductCategoryError, ResolveProductsResult, decimal_json,
+};
+use serde_json::{Value as JsonValue, json};
 
 pub fn transform(
-    elec_storage_heater: &mut Map<String, JsonValue>,
+    elec_storage_heater: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
     energy_supplies: &EnergySupplies,
@@ -23,33 +25,34 @@ pub fn transform(
         ..
     } = &product.technology
     {
-        elec_storage_heater.insert("pwr_in".into(), pwr_in.to_f64().into());
+        elec_storage_heater.insert("pwr_in".into(), decimal_json(pwr_in));
         elec_storage_heater.insert(
             "rated_power_instant".into(),
-            rated_power_instant.to_f64().into(),
+            decimal_json(rated_power_instant),
         );
-        elec_storage_heater.insert("storage_capacity".into(), storage_capacity.to_f64().into());
+        elec_storage_heater.insert("storage_capacity".into(), decimal_json(storage_capacity));
         elec_storage_heater.insert("air_flow_type".into(), air_flow_type.to_string().into());
-        elec_storage_heater.insert("frac_convective".into(), frac_convective.to_f64().into());
+        elec_storage_heater.insert("frac_convective".into(), decimal_json(frac_convective));
 
         let energy_supply = energy_supplies
             .get(fuel)
             .ok_or_else(|| ResolvePcdbProductsError::from(fuel))?;
         elec_storage_heater.insert("EnergySupply".into(), json!(energy_supply.as_ref()));
 
-        elec_storage_heater.insert("fan_pwr".into(), fan_pwr.to_f64().into());
+        elec_storage_heater.insert("fan_pwr".into(), decimal_json(fan_pwr));
 
-        let (dry_core_min_output, dry_core_max_output): (Vec<[f64; 2]>, Vec<[f64; 2]>) = test_data
-            .iter()
-            .map(|datum| {
-                let test_point = datum.test_point.as_f64();
+        let (dry_core_min_output, dry_core_max_output): (Vec<[JsonValue; 2]>, Vec<[JsonValue; 2]>) =
+            test_data
+                .iter()
+                .map(|datum| {
+                    let test_point = decimal_json(&datum.test_point);
 
-                (
-                    [test_point, datum.dry_core_min_output.as_f64()],
-                    [test_point, datum.dry_core_max_output.as_f64()],
-                )
-            })
-            .unzip();
+                    (
+                        [test_point.clone(), decimal_json(&datum.dry_core_min_output)],
+                        [test_point, decimal_json(&datum.dry_core_max_output)],
+                    )
+                })
+                .unzip();
 
         elec_storage_heater.insert("dry_core_min_output".into(), dry_core_min_output.into());
         elec_storage_heater.insert("dry_core_max_output".into(), dry_core_max_output.into());
@@ -73,7 +76,7 @@ mod tests {
     use crate::transform::catalogue::{mock_energy_supplies, transformed_input_matches_expected};
     use crate::transform::space_heat_system::tests::SPACE_HEATING_PCDB_PRODUCTS;
     use rstest::*;
-    use serde_json::{Value, from_str, json};
+    use serde_json::{Map, Value, from_str, json};
     use std::collections::HashMap;
 
     #[fixture]
@@ -98,7 +101,7 @@ mod tests {
             from_str(include_str!("../fixtures/esh_transformed.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             SPACE_HEATING_PCDB_PRODUCTS.get(product_reference).unwrap(),
             product_reference,
             &energy_supplies,
@@ -116,7 +119,7 @@ mod tests {
             from_str(include_str!("../fixtures/heat_pump_pcdb.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             pcdb_hps.get(product_reference).unwrap(),
             product_reference,
             &energy_supplies,
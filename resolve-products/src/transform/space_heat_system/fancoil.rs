@@ -1,14 +1,14 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::products::{FanCoilTestDatum, Product, Technology};
-use crate::transform::{InvalidProductCategoryError, TransformResult};
+use crate::transform::{InvalidProductCategoryError, TransformResult, decimal_json};
 use itertools::Itertools;
 use rust_decimal::Decimal;
-use serde::Serialize;
-use serde_json::{Map, Value as JsonValue, json};
+use serde_json::{Value as JsonValue, json};
 use std::collections::BTreeSet;
 
 pub fn transform(
-    fancoil: &mut Map<String, JsonValue>,
+    fancoil: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
 ) -> TransformResult {
@@ -18,7 +18,7 @@ pub fn transform(
         ..
     } = &product.technology
     {
-        fancoil.insert("frac_convective".into(), frac_convective.as_f64().into());
+        fancoil.insert("frac_convective".into(), decimal_json(frac_convective));
         fancoil.insert(
             "fancoil_test_data".into(),
             json!(test_data_for_target(test_data)),
@@ -48,13 +48,13 @@ fn test_data_for_target(test_data: &[FanCoilTestDatum]) -> JsonValue {
     }) {
         let fan_speed_datum = fan_speed_data
             .iter_mut()
-            .find(|datum| datum.temperature_diff == test_datum.temperature_diff.as_f64());
+            .find(|datum| datum.temperature_diff == test_datum.temperature_diff);
 
         if let Some(fan_speed_datum) = fan_speed_datum {
-            fan_speed_datum.add_power_output(test_datum.power_output.as_f64());
+            fan_speed_datum.add_power_output(test_datum.power_output);
         } else {
-            let mut new_datum = FanSpeedDatum::new(test_datum.temperature_diff.as_f64());
-            new_datum.add_power_output(test_datum.power_output.as_f64());
+            let mut new_datum = FanSpeedDatum::new(test_datum.temperature_diff);
+            new_datum.add_power_output(test_datum.power_output);
             fan_speed_data.push(new_datum);
         }
 
@@ -62,28 +62,35 @@ fn test_data_for_target(test_data: &[FanCoilTestDatum]) -> JsonValue {
     }
 
     json!({
-        "fan_speed_data": fan_speed_data,
-        "fan_power_W": fan_power_w.iter().map(Decimal::as_f64).collect_vec(),
+        "fan_speed_data": fan_speed_data.iter().map(FanSpeedDatum::to_json).collect_vec(),
+        "fan_power_W": fan_power_w.iter().map(decimal_json).collect_vec(),
     })
 }
 
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone)]
 struct FanSpeedDatum {
-    temperature_diff: f64,
-    power_output: Vec<f64>,
+    temperature_diff: Decimal,
+    power_output: Vec<Decimal>,
 }
 
 impl FanSpeedDatum {
-    fn new(temperature_diff: f64) -> Self {
+    fn new(temperature_diff: Decimal) -> Self {
         Self {
             temperature_diff,
             ..Default::default()
         }
     }
 
-    fn add_power_output(&mut self, power_output: f64) {
+    fn add_power_output(&mut self, power_output: Decimal) {
         self.power_output.push(power_output);
     }
+
+    fn to_json(&self) -> JsonValue {
+        json!({
+            "temperature_diff": decimal_json(&self.temperature_diff),
+            "power_output": self.power_output.iter().map(decimal_json).collect_vec(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -91,7 +98,7 @@ mod tests {
     use super::*;
     use crate::transform::catalogue::transformed_input_matches_expected;
     use crate::transform::space_heat_system::tests::SPACE_HEATING_PCDB_PRODUCTS;
-    use serde_json::{from_str, json};
+    use serde_json::{Map, from_str, json};
 
     fn input(product_reference: &str) -> JsonValue {
         json!({
@@ -109,7 +116,7 @@ mod tests {
             from_str(include_str!("../fixtures/fancoil_transformed.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             SPACE_HEATING_PCDB_PRODUCTS.get(product_reference).unwrap(),
             product_reference,
         );
@@ -4,82 +4,135 @@ mod radiator;
 mod underfloor_heating;
 
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
 use crate::products::Product;
-use crate::transform::{EnergySupplies, ResolveProductsResult, product_reference_from_json_object};
+use crate::transform::{
+    EnergySupplies, EntryErrors, ResolveProductsResult, json_pointer,
+    product_reference_from_json_object,
+};
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
+use crate::versions::VersionBehaviour;
 use serde_json::{Value as JsonValue, json};
 use smartstring::alias::String;
 use std::collections::HashMap;
 
+/// Resolves storage heater products on space heating systems, and emitter products on the emitters of wet distribution systems.
+pub(crate) struct SpaceHeatSystemTransformer;
+
+impl Transformer for SpaceHeatSystemTransformer {
+    fn name(&self) -> &'static str {
+        "space_heat_system"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![
+            TransformerInfo::new(
+                self.name(),
+                "/SpaceHeatSystem/*",
+                Some("ElecStorageHeater"),
+                &["StorageHeater"],
+            ),
+            TransformerInfo::new(
+                self.name(),
+                "/SpaceHeatSystem/*/emitters/*",
+                Some("radiator"),
+                &["ConvectorRadiator"],
+            )
+            .with_discriminator_field("wet_emitter_type"),
+            TransformerInfo::new(
+                self.name(),
+                "/SpaceHeatSystem/*/emitters/*",
+                Some("ufh"),
+                &["UnderFloorHeating"],
+            )
+            .with_discriminator_field("wet_emitter_type"),
+            TransformerInfo::new(
+                self.name(),
+                "/SpaceHeatSystem/*/emitters/*",
+                Some("fancoil"),
+                &["FanCoils"],
+            )
+            .with_discriminator_field("wet_emitter_type"),
+        ]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(async move {
+            transform(
+                json,
+                context.products,
+                context.energy_supplies,
+                context.version_behaviour,
+                context.errors,
+                context.conflicts,
+            )
+        })
+    }
+}
+
 pub fn transform(
     json: &mut JsonValue,
     products: &HashMap<String, Product>,
     energy_supplies: &EnergySupplies,
+    version_behaviour: &VersionBehaviour,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
 ) -> ResolveProductsResult<()> {
     let space_heat_systems = match json.pointer_mut("/SpaceHeatSystem") {
         Some(node) if node.is_object() => node.as_object_mut().unwrap(),
         _ => return Ok(()),
     };
 
-    for value in space_heat_systems.values_mut() {
+    for (name, value) in space_heat_systems.iter_mut() {
         if let JsonValue::Object(system) = value {
             if let Some(system_type) = system.get("type").and_then(|v| v.as_str()) {
                 match system_type {
                     "ElecStorageHeater" if system.contains_key(PRODUCT_REFERENCE_FIELD) => {
-                        let product_ref = product_reference_from_json_object(system)?;
-
-                        elec_storage_heater::transform(
-                            system,
-                            &products[&product_ref],
-                            &product_ref,
+                        let pointer = json_pointer(["SpaceHeatSystem", name]);
+                        let result = transform_elec_storage_heater(
+                            &mut ResolvedEntry::new(system, pointer.as_str(), conflicts),
+                            products,
                             energy_supplies,
-                        )?
+                        );
+                        errors.check(|| pointer, result)?;
                     }
                     "WetDistribution" => {
                         let emitters = system.get_mut("emitters").and_then(|v| v.as_array_mut());
-                        for value in emitters.into_iter().flatten() {
+                        for (index, value) in emitters.into_iter().flatten().enumerate() {
                             if let Some(emitter) = value.as_object_mut() {
-                                if emitter.contains_key(PRODUCT_REFERENCE_FIELD) {
-                                    let product_ref = product_reference_from_json_object(emitter)?;
-
-                                    if let Some(emitter_type) =
-                                        emitter.get("wet_emitter_type").and_then(|v| v.as_str())
-                                    {
-                                        match emitter_type {
-                                            "radiator" => radiator::transform(
-                                                emitter,
-                                                &products[&product_ref],
-                                                &product_ref,
-                                            )?,
-                                            "ufh" => underfloor_heating::transform(
-                                                emitter,
-                                                &products[&product_ref],
-                                                &product_ref,
-                                            )?,
-                                            "fancoil" => fancoil::transform(
-                                                emitter,
-                                                &products[&product_ref],
-                                                &product_ref,
-                                            )?,
-                                            _ => {}
-                                        }
-                                    }
-                                }
+                                let pointer = json_pointer([
+                                    "SpaceHeatSystem",
+                                    name,
+                                    "emitters",
+                                    &index.to_string(),
+                                ]);
+                                let result = transform_emitter(
+                                    &mut ResolvedEntry::new(emitter, pointer.as_str(), conflicts),
+                                    products,
+                                );
+                                errors.check(|| pointer, result)?;
                             }
                         }
 
-                        // NB. it should be that this following check, and behaviour, needs to be removed for 1.0.0-alpha9!
-
                         // for 1.0.0-alpha7, if there are any emitters with an 'n' field and "thermal_mass" isn't already
                         // set as a sibling field to emitters, then set one with the token non-zero value 0.001 because
                         // the schema needs it (this is fixed in subsequent versions of FHS)
-                        let emitters = system.get("emitters").and_then(|v| v.as_array());
-                        if emitters.into_iter().flatten().any(|emitter| {
-                            emitter.get("n").is_some() && !system.contains_key("thermal_mass")
-                        }) {
-                            system.insert("thermal_mass".to_string(), json!(0.001));
+                        if version_behaviour.emitter_thermal_mass_placeholder {
+                            let emitters = system.get("emitters").and_then(|v| v.as_array());
+                            if emitters.into_iter().flatten().any(|emitter| {
+                                emitter.get("n").is_some() && !system.contains_key("thermal_mass")
+                            }) {
+                                system.insert("thermal_mass".to_string(), json!(0.001));
+                            }
                         }
                     }
-                    _ => {} // TODO could add warning about unexpected type being reached
+                    // a product reference on any other type is left in place, and so fails the check
+                    // that every reference was consumed
+                    _ => {}
                 }
             }
         }
@@ -88,10 +141,49 @@ pub fn transform(
     Ok(())
 }
 
+fn transform_elec_storage_heater(
+    system: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+    energy_supplies: &EnergySupplies,
+) -> ResolveProductsResult<()> {
+    let product_ref = product_reference_from_json_object(system)?;
+
+    elec_storage_heater::transform(
+        system,
+        &products[&product_ref],
+        &product_ref,
+        energy_supplies,
+    )
+}
+
+fn transform_emitter(
+    emitter: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+) -> ResolveProductsResult<()> {
+    if emitter.contains_key(PRODUCT_REFERENCE_FIELD) {
+        let product_ref = product_reference_from_json_object(emitter)?;
+
+        if let Some(emitter_type) = emitter.get("wet_emitter_type").and_then(|v| v.as_str()) {
+            match emitter_type {
+                "radiator" => radiator::transform(emitter, &products[&product_ref], &product_ref)?,
+                "ufh" => {
+                    underfloor_heating::transform(emitter, &products[&product_ref], &product_ref)?
+                }
+                "fancoil" => fancoil::transform(emitter, &products[&product_ref], &product_ref)?,
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::transform::catalogue::mock_energy_supplies;
+    use crate::errors::ResolvePcdbProductsError;
+    use crate::transform::catalogue::{assert_json_eq, mock_energy_supplies};
+    use crate::transform::check_all_references_consumed;
     use crate::transform::space_heat_system::transform;
     use rstest::*;
     use serde_json::{from_str, json};
@@ -105,8 +197,18 @@ mod tests {
     pub(crate) static SPACE_HEATING_PCDB_PRODUCTS: LazyLock<HashMap<String, Product>> =
         LazyLock::new(|| from_str(include_str!("../fixtures/space_heating_pcdb.json")).unwrap());
 
+    #[fixture]
+    fn alpha7_behaviour() -> VersionBehaviour {
+        VersionBehaviour {
+            emitter_thermal_mass_placeholder: true,
+        }
+    }
+
     #[rstest]
-    fn test_transform_space_heating(energy_supplies: EnergySupplies) {
+    fn test_transform_space_heating(
+        energy_supplies: EnergySupplies,
+        alpha7_behaviour: VersionBehaviour,
+    ) {
         let mut input = from_str(include_str!("../fixtures/space_heating_input.json")).unwrap();
         let expected_esh: JsonValue =
             from_str(include_str!("../fixtures/esh_transformed.json")).unwrap();
@@ -115,7 +217,14 @@ mod tests {
         ))
         .unwrap();
 
-        let result = transform(&mut input, &SPACE_HEATING_PCDB_PRODUCTS, &energy_supplies);
+        let result = transform(
+            &mut input,
+            &SPACE_HEATING_PCDB_PRODUCTS,
+            &energy_supplies,
+            &alpha7_behaviour,
+            &mut Default::default(),
+            &mut Default::default(),
+        );
 
         let expected_input = json!({
             "SpaceHeatSystem": {
@@ -127,12 +236,65 @@ mod tests {
 
         assert!(result.is_ok());
 
-        assert_eq!(
-            input,
-            expected_input,
-            "actual: {}\nexpected: {}",
-            serde_json::to_string_pretty(&input).unwrap(),
-            serde_json::to_string_pretty(&expected_input).unwrap()
+        assert_json_eq(&input, &expected_input);
+    }
+
+    #[rstest]
+    fn test_transform_leaves_product_reference_on_unexpected_type_unconsumed(
+        energy_supplies: EnergySupplies,
+        alpha7_behaviour: VersionBehaviour,
+    ) {
+        let mut input = json!({
+            "SpaceHeatSystem": {
+                "instant": {
+                    "type": "InstantElecHeater",
+                    "product_reference": "instant-heater",
+                },
+                "unreferenced": {"type": "InstantElecHeater"},
+            }
+        });
+        let expected_input = input.clone();
+
+        let result = transform(
+            &mut input,
+            &SPACE_HEATING_PCDB_PRODUCTS,
+            &energy_supplies,
+            &alpha7_behaviour,
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(input, expected_input);
+        assert!(matches!(
+            check_all_references_consumed(&input),
+            Err(ResolvePcdbProductsError::UnconsumedProductReferences(pointers))
+                if pointers == ["/SpaceHeatSystem/instant/product_reference"]
+        ));
+    }
+
+    #[rstest]
+    fn test_transform_only_adds_thermal_mass_placeholder_for_versions_needing_it(
+        energy_supplies: EnergySupplies,
+    ) {
+        let mut input = from_str(include_str!("../fixtures/space_heating_input.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &SPACE_HEATING_PCDB_PRODUCTS,
+            &energy_supplies,
+            &VersionBehaviour {
+                emitter_thermal_mass_placeholder: false,
+            },
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert!(
+            input
+                .pointer("/SpaceHeatSystem/WetDistribution/thermal_mass")
+                .is_none()
         );
     }
 }
@@ -1,10 +1,10 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::products::{Product, Technology};
-use crate::transform::{InvalidProductCategoryError, TransformResult};
-use serde_json::{Map, Value as JsonValue};
+use crate::transform::{InvalidProductCategoryError, TransformResult, decimal_json};
 
 pub fn transform(
-    radiator: &mut Map<String, JsonValue>,
+    radiator: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
 ) -> TransformResult {
@@ -16,12 +16,12 @@ pub fn transform(
         ..
     } = &product.technology
     {
-        radiator.insert("n".into(), n.as_f64().into());
-        radiator.insert("frac_convective".into(), frac_convective.as_f64().into());
-        radiator.insert("c_per_m".into(), c_per_m.as_f64().into());
+        radiator.insert("n".into(), decimal_json(n));
+        radiator.insert("frac_convective".into(), decimal_json(frac_convective));
+        radiator.insert("c_per_m".into(), decimal_json(c_per_m));
         radiator.insert(
             "thermal_mass_per_m".into(),
-            thermal_mass_per_m.as_f64().into(),
+            decimal_json(thermal_mass_per_m),
         );
 
         // now remove product reference and radiator type
@@ -42,10 +42,10 @@ mod tests {
     use super::*;
     use crate::transform::catalogue::transformed_input_matches_expected;
     use crate::transform::space_heat_system::tests::SPACE_HEATING_PCDB_PRODUCTS;
-    use serde_json::{Value, from_str, json};
+    use serde_json::{Map, Value, from_str, json};
     use std::collections::HashMap;
 
-    fn input(product_reference: &str) -> JsonValue {
+    fn input(product_reference: &str) -> Value {
         json!({
             "wet_emitter_type": "radiator",
             "radiator_type": "standard",
@@ -62,7 +62,7 @@ mod tests {
             from_str(include_str!("../fixtures/radiator_transformed.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             SPACE_HEATING_PCDB_PRODUCTS.get(product_reference).unwrap(),
             product_reference,
         );
@@ -79,7 +79,7 @@ mod tests {
             from_str(include_str!("../fixtures/heat_pump_pcdb.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             pcdb_hps.get(product_reference).unwrap(),
             product_reference,
         );
@@ -1,10 +1,10 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::ResolvedEntry;
 use crate::products::{Product, Technology};
-use crate::transform::{InvalidProductCategoryError, TransformResult};
-use serde_json::{Map, Value as JsonValue};
+use crate::transform::{InvalidProductCategoryError, TransformResult, decimal_json};
 
 pub fn transform(
-    underfloor_heating: &mut Map<String, JsonValue>,
+    underfloor_heating: &mut ResolvedEntry<'_>,
     product: &Product,
     product_reference: &str,
 ) -> TransformResult {
@@ -17,12 +17,12 @@ pub fn transform(
     {
         underfloor_heating.insert(
             "system_performance_factor".into(),
-            system_performance_factor.as_f64().into(),
+            decimal_json(system_performance_factor),
         );
-        underfloor_heating.insert("frac_convective".into(), frac_convective.as_f64().into());
+        underfloor_heating.insert("frac_convective".into(), decimal_json(frac_convective));
         underfloor_heating.insert(
             "equivalent_specific_thermal_mass".into(),
-            equivalent_specific_thermal_mass.as_f64().into(),
+            decimal_json(equivalent_specific_thermal_mass),
         );
 
         // now remove product reference
@@ -42,9 +42,9 @@ mod tests {
     use super::*;
     use crate::transform::catalogue::transformed_input_matches_expected;
     use crate::transform::space_heat_system::tests::SPACE_HEATING_PCDB_PRODUCTS;
-    use serde_json::{from_str, json};
+    use serde_json::{Map, Value, from_str, json};
 
-    fn input(product_reference: &str) -> JsonValue {
+    fn input(product_reference: &str) -> Value {
         json!({
             "wet_emitter_type": "ufh",
             "product_reference": product_reference,
@@ -56,11 +56,11 @@ mod tests {
     fn test_transform_underfloor_heating() {
         let product_reference = "720";
         let mut input = input(product_reference);
-        let expected: Map<String, JsonValue> =
+        let expected: Map<String, Value> =
             from_str(include_str!("../fixtures/ufh_transformed.json")).unwrap();
 
         let result = transform(
-            input.as_object_mut().unwrap(),
+            &mut ResolvedEntry::new(input.as_object_mut().unwrap(), "", &mut Default::default()),
             SPACE_HEATING_PCDB_PRODUCTS.get(product_reference).unwrap(),
             product_reference,
         );
@@ -0,0 +1,219 @@
+use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
+use crate::errors::ResolvePcdbProductsError;
+use crate::products::{Product, Technology};
+use crate::transform::{
+    EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json, json_pointer,
+    product_reference_from_json_object,
+};
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
+use serde_json::Value as JsonValue;
+use smartstring::alias::String;
+use std::collections::HashMap;
+
+/// Resolves hot water cylinder products on the hot water storage tank.
+pub(crate) struct StorageTankTransformer;
+
+impl Transformer for StorageTankTransformer {
+    fn name(&self) -> &'static str {
+        "storage_tank"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![TransformerInfo::new(
+            self.name(),
+            "/HotWaterSource/hw cylinder",
+            Some("StorageTank"),
+            &["HotWaterCylinder"],
+        )]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(
+            async move { transform(json, context.products, context.errors, context.conflicts) },
+        )
+    }
+}
+
+pub(crate) fn transform(
+    json: &mut JsonValue,
+    products: &HashMap<String, Product>,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
+) -> ResolveProductsResult<()> {
+    let hot_water_source = match json.pointer_mut("/HotWaterSource/hw cylinder") {
+        Some(node) if node.is_object() => node.as_object_mut().unwrap(),
+        _ => return Ok(()),
+    };
+
+    let pointer = json_pointer(["HotWaterSource", "hw cylinder"]);
+    let result = transform_hot_water_source(
+        &mut ResolvedEntry::new(hot_water_source, pointer.as_str(), conflicts),
+        products,
+    );
+    errors.check(|| pointer, result)
+}
+
+fn transform_hot_water_source(
+    hot_water_source: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+) -> ResolveProductsResult<()> {
+    if hot_water_source.get("type").and_then(JsonValue::as_str) != Some("StorageTank")
+        || !hot_water_source.contains_key(PRODUCT_REFERENCE_FIELD)
+    {
+        return Ok(());
+    }
+
+    let product_reference = product_reference_from_json_object(hot_water_source)?;
+    let product = &products[&product_reference];
+    // the schema only takes a heat exchanger area for a cylinder heated by hot water only heat pumps
+    let has_only_hp_hw_only = hot_water_source
+        .get("HeatSource")
+        .and_then(JsonValue::as_object)
+        .is_some_and(|heat_sources| {
+            heat_sources
+                .values()
+                .all(|heat_source| heat_source["type"] == "HeatPump_HWOnly")
+        });
+
+    if let Technology::StorageTank {
+        volume_declared,
+        daily_losses_declared,
+        heat_exchanger_surface_area_declared,
+    } = &product.technology
+    {
+        if has_only_hp_hw_only {
+            let heat_exchanger_surface_area = heat_exchanger_surface_area_declared.ok_or_else(
+                || ResolvePcdbProductsError::InvalidCombination(
+                    "heat_exchanger_surface_area required for storage tank with a hot water only heat pump as its heat source".into(),
+                ),
+            )?;
+            hot_water_source.insert(
+                "heat_exchanger_surface_area".into(),
+                decimal_json(&heat_exchanger_surface_area),
+            );
+        }
+        hot_water_source.insert("volume".into(), decimal_json(volume_declared));
+        hot_water_source.insert("daily_losses".into(), decimal_json(daily_losses_declared));
+
+        // now remove product reference
+        hot_water_source.remove(PRODUCT_REFERENCE_FIELD);
+    } else {
+        return Err(InvalidProductCategoryError::from((product_reference, "storage tank")).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::catalogue::assert_json_eq;
+    use serde_json::{from_str, json};
+
+    fn input(product_reference: &str, heat_source: JsonValue) -> JsonValue {
+        json!({
+            "HotWaterSource": {
+                "hw cylinder": {
+                    "type": "StorageTank",
+                    "product_reference": product_reference,
+                    "ColdWaterSource": "mains water",
+                    "HeatSource": {"heat source": heat_source}
+                }
+            },
+        })
+    }
+
+    fn boiler_heat_source() -> JsonValue {
+        json!({
+            "type": "HeatSourceWet",
+            "name": "boiler",
+            "heater_position": 0.1,
+            "thermostat_position": 0.33
+        })
+    }
+
+    fn pcdb_cylinders() -> HashMap<String, Product> {
+        from_str(include_str!("fixtures/storage_tank_pcdb.json")).unwrap()
+    }
+
+    #[test]
+    fn test_transform_storage_tank() {
+        let mut input = input("cylinder", boiler_heat_source());
+        let expected: JsonValue =
+            from_str(include_str!("fixtures/storage_tank_transformed.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &pcdb_cylinders(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert_json_eq(&input, &expected);
+    }
+
+    #[test]
+    fn test_transform_storage_tank_heated_by_hot_water_only_heat_pump() {
+        let mut input = input(
+            "cylinder",
+            json!({"type": "HeatPump_HWOnly", "product_reference": "hp_hw_only"}),
+        );
+
+        let result = transform(
+            &mut input,
+            &pcdb_cylinders(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            input["HotWaterSource"]["hw cylinder"]["heat_exchanger_surface_area"],
+            json!(1.5)
+        );
+    }
+
+    #[test]
+    fn test_transform_storage_tank_errors_without_heat_exchanger_for_hot_water_only_heat_pump() {
+        let mut input = input(
+            "cylinder_without_heat_exchanger",
+            json!({"type": "HeatPump_HWOnly", "product_reference": "hp_hw_only"}),
+        );
+
+        let result = transform(
+            &mut input,
+            &pcdb_cylinders(),
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::InvalidCombination(_))
+        ));
+    }
+
+    #[test]
+    fn test_transform_storage_tank_errors_when_product_type_mismatch() {
+        let product_reference = "hp";
+        let mut input = input(product_reference, boiler_heat_source());
+        let pcdb_hps: HashMap<String, Product> =
+            from_str(include_str!("fixtures/heat_pump_pcdb.json")).unwrap();
+
+        let result = transform(
+            &mut input,
+            &pcdb_hps,
+            &mut Default::default(),
+            &mut Default::default(),
+        );
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("storage tank"));
+    }
+}
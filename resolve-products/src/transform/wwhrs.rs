@@ -1,85 +1,124 @@
 use crate::PRODUCT_REFERENCE_FIELD;
+use crate::conflicts::{Conflicts, ResolvedEntry};
 use crate::errors::ResolvePcdbProductsError;
 use crate::products::{Product, Technology, WwhrsSystemType};
 use crate::transform::{
-    InvalidProductCategoryError, ResolveProductsResult, product_reference_from_json_object,
+    EntryErrors, InvalidProductCategoryError, ResolveProductsResult, decimal_json, json_pointer,
+    product_reference_from_json_object,
 };
+use crate::transformer::{DocumentContext, TransformFuture, Transformer, TransformerInfo};
 use itertools::Itertools;
 use serde_json::Value as JsonValue;
 use smartstring::alias::String;
 use std::collections::HashMap;
 
+/// Resolves instantaneous waste water heat recovery system products.
+pub(crate) struct WwhrsTransformer;
+
+impl Transformer for WwhrsTransformer {
+    fn name(&self) -> &'static str {
+        "wwhrs"
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![TransformerInfo::new(
+            self.name(),
+            "/WWHRS/*",
+            None,
+            &["InstantaneousWwhrSystem"],
+        )]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(
+            async move { transform(json, context.products, context.errors, context.conflicts) },
+        )
+    }
+}
+
 pub fn transform(
     json: &mut JsonValue,
     products: &HashMap<String, Product>,
+    errors: &mut EntryErrors,
+    conflicts: &mut Conflicts,
 ) -> ResolveProductsResult<()> {
     let wwhrs_systems = match json.pointer_mut("/WWHRS") {
         Some(node) if node.is_object() => node.as_object_mut().unwrap(),
         _ => return Ok(()),
     };
 
-    for value in wwhrs_systems.values_mut() {
+    for (name, value) in wwhrs_systems.iter_mut() {
         if let JsonValue::Object(wwhrs) = value {
-            if wwhrs.contains_key(PRODUCT_REFERENCE_FIELD) {
-                let product_reference = product_reference_from_json_object(wwhrs)?;
-                let product = &products[&product_reference];
-
-                if let Technology::Wwhrs {
-                    test_data,
-                    utilisation_factor,
-                    ..
-                } = &product.technology
-                {
-                    wwhrs.insert("type".into(), "WWHRS_Instantaneous".into());
-
-                    let system_type: WwhrsSystemType = test_data
-                        .first()
-                        .ok_or_else(|| {
-                            ResolvePcdbProductsError::InvalidProduct(
-                                product_reference.to_string(),
-                                "WWHRS test data was not expected to be empty",
-                            )
-                        })?
-                        .system_type;
-
-                    let (flow_rates, system_efficiencies): (Vec<f64>, Vec<f64>) = test_data
-                        .iter()
-                        .sorted_by(|a, b| a.flow_rate.cmp(&b.flow_rate))
-                        .map(|test_datum| {
-                            (
-                                test_datum.flow_rate.as_f64(),
-                                test_datum.efficiency.as_f64(),
-                            )
-                        })
-                        .unzip();
-
-                    let (efficiencies_field, utilisation_factor_field) = match system_type {
-                        WwhrsSystemType::A => {
-                            ("system_a_efficiencies", "system_a_utilisation_factor")
-                        }
-                        WwhrsSystemType::B => {
-                            ("system_b_efficiencies", "system_b_utilisation_factor")
-                        }
-                        WwhrsSystemType::C => {
-                            ("system_c_efficiencies", "system_c_utilisation_factor")
-                        }
-                    };
-
-                    wwhrs.insert("flow_rates".into(), flow_rates.into());
-                    wwhrs.insert(efficiencies_field.into(), system_efficiencies.into());
-                    wwhrs.insert(
-                        utilisation_factor_field.into(),
-                        utilisation_factor.as_f64().into(),
-                    );
-
-                    // now remove product reference
-                    wwhrs.remove(PRODUCT_REFERENCE_FIELD);
-                } else {
-                    return Err(
-                        InvalidProductCategoryError::from((product_reference, "WWHRS")).into(),
-                    );
-                }
-            }
+            let pointer = json_pointer(["WWHRS", name]);
+            let result = transform_wwhrs(
+                &mut ResolvedEntry::new(wwhrs, pointer.as_str(), conflicts),
+                products,
+            );
+            errors.check(|| pointer, result)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn transform_wwhrs(
+    wwhrs: &mut ResolvedEntry<'_>,
+    products: &HashMap<String, Product>,
+) -> ResolveProductsResult<()> {
+    if wwhrs.contains_key(PRODUCT_REFERENCE_FIELD) {
+        let product_reference = product_reference_from_json_object(wwhrs)?;
+        let product = &products[&product_reference];
+
+        if let Technology::Wwhrs {
+            test_data,
+            utilisation_factor,
+            ..
+        } = &product.technology
+        {
+            wwhrs.insert("type".into(), "WWHRS_Instantaneous".into());
+
+            let system_type: WwhrsSystemType = test_data
+                .first()
+                .ok_or_else(|| {
+                    ResolvePcdbProductsError::InvalidProduct(
+                        product_reference.to_string(),
+                        "WWHRS test data was not expected to be empty",
+                    )
+                })?
+                .system_type;
+
+            let (flow_rates, system_efficiencies): (Vec<JsonValue>, Vec<JsonValue>) = test_data
+                .iter()
+                .sorted_by(|a, b| a.flow_rate.cmp(&b.flow_rate))
+                .map(|test_datum| {
+                    (
+                        decimal_json(&test_datum.flow_rate),
+                        decimal_json(&test_datum.efficiency),
+                    )
+                })
+                .unzip();
+
+            let (efficiencies_field, utilisation_factor_field) = match system_type {
+                WwhrsSystemType::A => ("system_a_efficiencies", "system_a_utilisation_factor"),
+                WwhrsSystemType::B => ("system_b_efficiencies", "system_b_utilisation_factor"),
+                WwhrsSystemType::C => ("system_c_efficiencies", "system_c_utilisation_factor"),
+            };
+
+            wwhrs.insert("flow_rates".into(), flow_rates.into());
+            wwhrs.insert(efficiencies_field.into(), system_efficiencies.into());
+            wwhrs.insert(
+                utilisation_factor_field.into(),
+                decimal_json(utilisation_factor),
+            );
+
+            // now remove product reference
+            wwhrs.remove(PRODUCT_REFERENCE_FIELD);
+        } else {
+            return Err(InvalidProductCategoryError::from((product_reference, "WWHRS")).into());
         }
     }
 
@@ -89,6 +128,7 @@ pub fn transform(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transform::catalogue::assert_json_eq;
     use rstest::rstest;
     use serde_json::{from_str, json};
     use std::collections::HashMap;
@@ -124,16 +164,12 @@ mod tests {
         let result = transform(
             &mut input,
             &HashMap::from([(product_reference.into(), pcdb_wwhrs)]),
+            &mut Default::default(),
+            &mut Default::default(),
         );
 
         assert!(result.is_ok());
-        assert_eq!(
-            input,
-            expected,
-            "actual: {}\nexpected: {}",
-            serde_json::to_string_pretty(&input).unwrap(),
-            serde_json::to_string_pretty(&expected).unwrap()
-        );
+        assert_json_eq(&input, &expected);
     }
 
     #[test]
@@ -143,7 +179,12 @@ mod tests {
         let pcdb_hps: HashMap<String, Product> =
             from_str(include_str!("fixtures/heat_pump_pcdb.json")).unwrap();
 
-        let result = transform(&mut input, &pcdb_hps);
+        let result = transform(
+            &mut input,
+            &pcdb_hps,
+            &mut Default::default(),
+            &mut Default::default(),
+        );
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("WWHRS"));
@@ -0,0 +1,613 @@
+//! module provides the transformers that resolve product references, and the extension point for
+//! resolving references in other ways (e.g. to in-house trial products, or to products of a known
+//! technology type that are to be resolved differently) without needing to change the crate itself
+
+use crate::PRODUCT_REFERENCE_FIELD;
+use crate::ResolveProductsResult;
+use crate::conflicts::{Conflicts, ResolvedEntry};
+use crate::errors::ResolvePcdbProductsError;
+use crate::in_use_factors::DynInUseFactorsAccess;
+use crate::products::{DynProductCatalogue, FuelType, Product};
+use crate::report::push_pointer_token;
+use crate::transform::{
+    AirPoweredShowerTransformer, ElectricBatteryTransformer, EnergySupplies, EntryErrors,
+    HeatPumpHotWaterOnlyTransformer, HeatSourceWetTransformer, MechanicalVentilationTransformer,
+    PhotovoltaicTransformer, SmartHotWaterTankTransformer, SolarThermalTransformer,
+    SpaceCoolSystemTransformer, SpaceHeatSystemTransformer, StorageTankTransformer,
+    WwhrsTransformer, product_reference_from_json_object,
+};
+use crate::versions::VersionBehaviour;
+use crate::warnings::{WarningCode, Warnings};
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use smartstring::alias::String as SmartString;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Resolves the product references in a document that it handles.
+///
+/// Each of the crate's own transform modules is a transformer, as is every [`EntryTransformer`].
+/// A transformer removes the reference fields it has resolved, so that transformers run after it
+/// leave those entries alone.
+pub trait Transformer: Send + Sync {
+    /// A name for the transformer, used in reports.
+    fn name(&self) -> &'static str;
+
+    /// Describes which product references the transformer resolves.
+    fn infos(&self) -> Vec<TransformerInfo>;
+
+    /// Resolves every product reference in the document that the transformer handles.
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a>;
+}
+
+/// The future returned by [`Transformer::transform_document`].
+pub type TransformFuture<'a> = Pin<Box<dyn Future<Output = ResolveProductsResult<()>> + Send + 'a>>;
+
+/// Resolves product references at one location in a document to products of given technology
+/// types, one entry at a time.
+///
+/// A transformer is given each entry in the document that is at its [`location`](Self::location),
+/// has its [`type_discriminator`](Self::type_discriminator) (if any) as the value of its `type`
+/// field, and holds a `product_reference` to a product of one of its
+/// [`technology_types`](Self::technology_types). The `product_reference` field is removed from the
+/// entry once the transformer has succeeded.
+pub trait EntryTransformer: Send + Sync {
+    /// A name for the transformer, used in reports.
+    fn name(&self) -> &'static str;
+
+    /// A pattern for the JSON pointers of the entries the transformer handles, where `*` stands for
+    /// any single key or array index (e.g. `/SpaceHeatSystem/*`).
+    fn location(&self) -> &'static str;
+
+    /// The value of the `type` field that entries must have to be handled, if entries of any type
+    /// at the location can be.
+    fn type_discriminator(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The PCDB technology types (e.g. "AirSourceHeatPump") of the products the transformer accepts.
+    fn technology_types(&self) -> &'static [&'static str];
+
+    /// Writes the fields for the given product into the entry.
+    fn transform(
+        &self,
+        entry: &mut ResolvedEntry<'_>,
+        product: &Product,
+        context: &mut TransformContext<'_>,
+    ) -> ResolveProductsResult<()>;
+
+    fn info(&self) -> TransformerInfo {
+        TransformerInfo::new(
+            self.name(),
+            self.location(),
+            self.type_discriminator(),
+            self.technology_types(),
+        )
+    }
+}
+
+impl<T: EntryTransformer> Transformer for T {
+    fn name(&self) -> &'static str {
+        EntryTransformer::name(self)
+    }
+
+    fn infos(&self) -> Vec<TransformerInfo> {
+        vec![self.info()]
+    }
+
+    fn transform_document<'a>(
+        &'a self,
+        json: &'a mut JsonValue,
+        context: &'a mut DocumentContext<'_>,
+    ) -> TransformFuture<'a> {
+        Box::pin(async move { apply_entry_transformer(self, json, context) })
+    }
+}
+
+/// A description of which product references a transformer resolves.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TransformerInfo {
+    pub name: &'static str,
+    /// A pattern for the JSON pointers of the entries the transformer handles, where `*` stands for
+    /// any single key or array index.
+    pub location: &'static str,
+    /// The field of the entry that holds the product reference (e.g. `product_reference`).
+    pub reference_field: &'static str,
+    /// The field of the entry whose value decides how it is handled (usually `type`).
+    pub discriminator_field: &'static str,
+    /// The value of the discriminator field that entries must have to be handled, if any.
+    pub type_discriminator: Option<&'static str>,
+    pub technology_types: &'static [&'static str],
+}
+
+impl TransformerInfo {
+    /// Describes a transformer handling references held in `product_reference`, discriminated by
+    /// the entry's `type`.
+    pub const fn new(
+        name: &'static str,
+        location: &'static str,
+        type_discriminator: Option<&'static str>,
+        technology_types: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            location,
+            reference_field: PRODUCT_REFERENCE_FIELD,
+            discriminator_field: "type",
+            type_discriminator,
+            technology_types,
+        }
+    }
+
+    pub const fn with_reference_field(self, reference_field: &'static str) -> Self {
+        Self {
+            reference_field,
+            ..self
+        }
+    }
+
+    pub const fn with_discriminator_field(self, discriminator_field: &'static str) -> Self {
+        Self {
+            discriminator_field,
+            ..self
+        }
+    }
+
+    /// Whether the given entry at the given JSON pointer, holding a reference in the given field to
+    /// a product of the given technology type, would be handled.
+    pub fn applies_to(
+        &self,
+        pointer: &str,
+        field: &str,
+        entry: &Map<String, JsonValue>,
+        technology_type: &str,
+    ) -> bool {
+        location_matches(self.location, pointer)
+            && field == self.reference_field
+            && self.type_discriminator.is_none_or(|discriminator| {
+                entry
+                    .get(self.discriminator_field)
+                    .and_then(JsonValue::as_str)
+                    == Some(discriminator)
+            })
+            && self.technology_types.contains(&technology_type)
+    }
+}
+
+/// What a [`Transformer`] is given alongside the document it is transforming.
+pub struct DocumentContext<'a> {
+    pub(crate) products: &'a HashMap<SmartString, Product>,
+    pub(crate) product_catalogue: &'a dyn DynProductCatalogue,
+    pub(crate) in_use_factors_access: &'a dyn DynInUseFactorsAccess,
+    pub(crate) energy_supplies: &'a EnergySupplies,
+    pub(crate) version_behaviour: &'a VersionBehaviour,
+    pub(crate) errors: &'a mut EntryErrors,
+    pub(crate) warnings: &'a mut Warnings,
+    pub(crate) conflicts: &'a mut Conflicts,
+}
+
+impl DocumentContext<'_> {
+    /// The product that the given reference in the document refers to.
+    pub fn product(&self, product_reference: &str) -> Option<&Product> {
+        self.products.get(product_reference)
+    }
+
+    /// The name of the energy supply in the document that provides the given fuel.
+    pub fn energy_supply(&self, fuel: FuelType) -> ResolveProductsResult<&str> {
+        energy_supply(self.energy_supplies, fuel)
+    }
+
+    /// The entry of the document at the given JSON pointer, for writing resolved values into.
+    pub fn entry<'e>(
+        &'e mut self,
+        fields: &'e mut Map<std::string::String, JsonValue>,
+        pointer: impl Into<std::string::String>,
+    ) -> ResolvedEntry<'e> {
+        ResolvedEntry::new(fields, pointer, self.conflicts)
+    }
+
+    /// Records an error in resolving the entry at the given JSON pointer, so that the rest of the
+    /// document can still be resolved when the resolver is collecting all errors. Otherwise, the
+    /// error is returned.
+    pub fn check(
+        &mut self,
+        pointer: impl FnOnce() -> std::string::String,
+        result: ResolveProductsResult<()>,
+    ) -> ResolveProductsResult<()> {
+        self.errors.check(pointer, result)
+    }
+
+    /// Raises a warning about the entry at the given JSON pointer.
+    pub fn warn(
+        &mut self,
+        pointer: impl Into<std::string::String>,
+        code: &'static str,
+        message: impl Into<std::string::String>,
+    ) {
+        self.warnings
+            .push(WarningCode::Custom(code), pointer.into(), message);
+    }
+}
+
+/// What an [`EntryTransformer`] is given alongside the entry and product it is transforming.
+pub struct TransformContext<'a> {
+    pointer: &'a str,
+    product_reference: &'a str,
+    energy_supplies: &'a EnergySupplies,
+    warnings: &'a mut Warnings,
+}
+
+impl TransformContext<'_> {
+    /// JSON pointer to the entry being transformed.
+    pub fn pointer(&self) -> &str {
+        self.pointer
+    }
+
+    pub fn product_reference(&self) -> &str {
+        self.product_reference
+    }
+
+    /// The name of the energy supply in the document that provides the given fuel.
+    pub fn energy_supply(&self, fuel: FuelType) -> ResolveProductsResult<&str> {
+        energy_supply(self.energy_supplies, fuel)
+    }
+
+    /// Raises a warning about the entry being transformed.
+    pub fn warn(&mut self, code: &'static str, message: impl Into<std::string::String>) {
+        self.warnings
+            .push(WarningCode::Custom(code), self.pointer.to_owned(), message);
+    }
+}
+
+fn energy_supply(energy_supplies: &EnergySupplies, fuel: FuelType) -> ResolveProductsResult<&str> {
+    energy_supplies
+        .get(&fuel)
+        .map(AsRef::as_ref)
+        .ok_or_else(|| ResolvePcdbProductsError::from(&fuel))
+}
+
+/// The transformers that resolve product references in a document, in the order they are run.
+///
+/// A new registry holds the built-in transformers. Custom transformers are run before the built-in
+/// ones, in the order they were registered, so that they can take over entries that a built-in
+/// transformer would otherwise resolve, or reject as holding a product of an unexpected category.
+pub struct TransformerRegistry {
+    transformers: Vec<Arc<dyn Transformer>>,
+    custom_count: usize,
+}
+
+impl TransformerRegistry {
+    pub fn new() -> Self {
+        Self {
+            transformers: vec![
+                Arc::new(HeatSourceWetTransformer),
+                Arc::new(SpaceHeatSystemTransformer),
+                Arc::new(SpaceCoolSystemTransformer),
+                Arc::new(WwhrsTransformer),
+                Arc::new(HeatPumpHotWaterOnlyTransformer),
+                Arc::new(SmartHotWaterTankTransformer),
+                Arc::new(StorageTankTransformer),
+                Arc::new(SolarThermalTransformer),
+                Arc::new(MechanicalVentilationTransformer),
+                Arc::new(AirPoweredShowerTransformer),
+                Arc::new(PhotovoltaicTransformer),
+                Arc::new(ElectricBatteryTransformer),
+            ],
+            custom_count: 0,
+        }
+    }
+
+    pub fn register(&mut self, transformer: impl Transformer + 'static) -> &mut Self {
+        self.transformers
+            .insert(self.custom_count, Arc::new(transformer));
+        self.custom_count += 1;
+        self
+    }
+
+    /// Describes every transformer in the registry, in the order they are run.
+    pub fn transformers(&self) -> Vec<TransformerInfo> {
+        self.transformers
+            .iter()
+            .flat_map(|transformer| transformer.infos())
+            .collect()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Arc<dyn Transformer>> {
+        self.transformers.iter()
+    }
+
+    /// Whether any custom transformer accepts products of the given technology type.
+    pub(crate) fn has_custom_transformer_for(&self, technology_type: &str) -> bool {
+        self.transformers[..self.custom_count]
+            .iter()
+            .flat_map(|transformer| transformer.infos())
+            .any(|info| info.technology_types.contains(&technology_type))
+    }
+}
+
+impl Default for TransformerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for TransformerRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.transformers()).finish()
+    }
+}
+
+/// Runs an entry transformer over every entry in the document that it handles.
+fn apply_entry_transformer(
+    transformer: &impl EntryTransformer,
+    json: &mut JsonValue,
+    context: &mut DocumentContext<'_>,
+) -> ResolveProductsResult<()> {
+    let info = transformer.info();
+    let tokens: Vec<_> = location_tokens(info.location).collect();
+    let mut entries = vec![];
+    entries_at(json, &tokens, &mut std::string::String::new(), &mut entries);
+
+    for (pointer, entry) in entries {
+        let Some(product) = entry
+            .get(PRODUCT_REFERENCE_FIELD)
+            .and_then(JsonValue::as_str)
+            .and_then(|product_reference| context.products.get(product_reference))
+        else {
+            continue;
+        };
+        if !info.applies_to(
+            &pointer,
+            PRODUCT_REFERENCE_FIELD,
+            entry,
+            product.technology_type(),
+        ) {
+            continue;
+        }
+
+        let result = product_reference_from_json_object(entry).and_then(|product_reference| {
+            let mut entry_context = TransformContext {
+                pointer: &pointer,
+                product_reference: &product_reference,
+                energy_supplies: context.energy_supplies,
+                warnings: context.warnings,
+            };
+            let mut entry = ResolvedEntry::new(entry, pointer.as_str(), context.conflicts);
+            transformer.transform(&mut entry, product, &mut entry_context)?;
+            entry.remove(PRODUCT_REFERENCE_FIELD);
+            Ok(())
+        });
+        context.errors.check(|| pointer, result)?;
+    }
+
+    Ok(())
+}
+
+fn location_tokens(location: &str) -> impl Iterator<Item = std::string::String> + '_ {
+    location
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+}
+
+/// Whether the given JSON pointer matches a location pattern, where `*` stands for any single key
+/// or array index.
+pub(crate) fn location_matches(location: &str, pointer: &str) -> bool {
+    let mut pattern = location_tokens(location);
+    let mut tokens = location_tokens(pointer);
+    loop {
+        match (pattern.next(), tokens.next()) {
+            (None, None) => return true,
+            (Some(expected), Some(token)) if expected == "*" || expected == token => {}
+            _ => return false,
+        }
+    }
+}
+
+fn entries_at<'a>(
+    json: &'a mut JsonValue,
+    tokens: &[std::string::String],
+    pointer: &mut std::string::String,
+    entries: &mut Vec<(
+        std::string::String,
+        &'a mut Map<std::string::String, JsonValue>,
+    )>,
+) {
+    let Some((token, rest)) = tokens.split_first() else {
+        if let JsonValue::Object(entry) = json {
+            entries.push((pointer.clone(), entry));
+        }
+        return;
+    };
+
+    let children: Vec<(std::string::String, &mut JsonValue)> = match json {
+        JsonValue::Object(object) => {
+            if token == "*" {
+                object
+                    .iter_mut()
+                    .map(|(key, value)| (key.clone(), value))
+                    .collect()
+            } else {
+                object
+                    .get_mut(token)
+                    .map(|value| (token.clone(), value))
+                    .into_iter()
+                    .collect()
+            }
+        }
+        JsonValue::Array(values) => {
+            if token == "*" {
+                values
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(index, value)| (index.to_string(), value))
+                    .collect()
+            } else {
+                token
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| values.get_mut(index))
+                    .map(|value| (token.clone(), value))
+                    .into_iter()
+                    .collect()
+            }
+        }
+        _ => vec![],
+    };
+
+    for (key, value) in children {
+        let length = pointer.len();
+        push_pointer_token(pointer, &key);
+        entries_at(value, rest, pointer, entries);
+        pointer.truncate(length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflicts::{ConflictPolicy, FieldConflict};
+    use crate::in_use_factors::mocks::FixtureBackedInUseFactorsAccess;
+    use crate::transform::catalogue::FixtureBackedProductCatalogue;
+    use rstest::rstest;
+    use serde_json::json;
+
+    struct TrialHeatPump;
+
+    impl EntryTransformer for TrialHeatPump {
+        fn name(&self) -> &'static str {
+            "trial_heat_pump"
+        }
+
+        fn location(&self) -> &'static str {
+            "/HeatSourceWet/*"
+        }
+
+        fn type_discriminator(&self) -> Option<&'static str> {
+            Some("HeatPump")
+        }
+
+        fn technology_types(&self) -> &'static [&'static str] {
+            &["TrialHeatPump"]
+        }
+
+        fn transform(
+            &self,
+            entry: &mut ResolvedEntry<'_>,
+            product: &Product,
+            context: &mut TransformContext<'_>,
+        ) -> ResolveProductsResult<()> {
+            let record = product.record();
+            entry.insert("power".into(), record["power"].clone());
+            entry.insert(
+                "EnergySupply".into(),
+                context.energy_supply(FuelType::Electricity)?.into(),
+            );
+            context.warn("trial_product", "trial products are not certified");
+            Ok(())
+        }
+    }
+
+    fn trial_products() -> HashMap<SmartString, Product> {
+        serde_json::from_value(json!({
+            "trial": {
+                "id": "1",
+                "technologyType": "TrialHeatPump",
+                "power": 5.5,
+            }
+        }))
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case(
+        "/SpaceHeatSystem/*/emitters/*",
+        "/SpaceHeatSystem/wet/emitters/0",
+        true
+    )]
+    #[case("/SpaceHeatSystem/*", "/SpaceHeatSystem/wet/emitters/0", false)]
+    #[case("/HotWaterSource/hw cylinder", "/HotWaterSource/hw cylinder", true)]
+    #[case("/a~1b/*", "/a~1b/c", true)]
+    #[case("/HeatSourceWet/*", "/HotWaterSource/hp", false)]
+    fn test_location_matches(
+        #[case] location: &str,
+        #[case] pointer: &str,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(location_matches(location, pointer), expected);
+    }
+
+    #[test]
+    fn test_custom_transformers_run_before_built_in_transformers() {
+        let mut registry = TransformerRegistry::new();
+        registry.register(TrialHeatPump);
+
+        assert_eq!(registry.iter().next().unwrap().name(), "trial_heat_pump");
+        assert!(registry.has_custom_transformer_for("TrialHeatPump"));
+        assert!(!registry.has_custom_transformer_for("AirSourceHeatPump"));
+        assert!(
+            registry
+                .transformers()
+                .iter()
+                .any(|info| info.name == "heat_source_wet")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entry_transformer_applies_to_matching_entries() {
+        let mut json = json!({
+            "HeatSourceWet": {
+                "trial": {"type": "HeatPump", "product_reference": "trial", "power": 4},
+                "boiler": {"type": "Boiler", "product_reference": "trial"},
+                "unreferenced": {"type": "HeatPump"},
+            }
+        });
+        let mut warnings = Warnings::default();
+        let mut conflicts = Conflicts::new(ConflictPolicy::UserWins);
+        let mut context = DocumentContext {
+            products: &trial_products(),
+            product_catalogue: &FixtureBackedProductCatalogue::new(),
+            in_use_factors_access: &FixtureBackedInUseFactorsAccess,
+            energy_supplies: &HashMap::from([(FuelType::Electricity, Arc::from("mains elec"))]),
+            version_behaviour: &Default::default(),
+            errors: &mut Default::default(),
+            warnings: &mut warnings,
+            conflicts: &mut conflicts,
+        };
+
+        TrialHeatPump
+            .transform_document(&mut json, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            json,
+            json!({
+                "HeatSourceWet": {
+                    "trial": {"type": "HeatPump", "power": 4, "EnergySupply": "mains elec"},
+                    "boiler": {"type": "Boiler", "product_reference": "trial"},
+                    "unreferenced": {"type": "HeatPump"},
+                }
+            })
+        );
+        let warnings = warnings.into_vec();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code.as_str(), "trial_product");
+        assert_eq!(warnings[0].pointer, "/HeatSourceWet/trial");
+        assert_eq!(
+            conflicts.into_result().unwrap(),
+            [FieldConflict {
+                pointer: "/HeatSourceWet/trial/power".into(),
+                input_value: json!(4),
+                pcdb_value: json!(5.5),
+            }]
+        );
+    }
+}
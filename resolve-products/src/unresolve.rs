@@ -0,0 +1,173 @@
+//! module provides the reverse of resolution, collapsing the fields resolved from the PCDB back into
+//! the product references they came from
+//!
+//! This works from the [`ResolutionReport`] made when the document was resolved, undoing the changes
+//! each transform module made in the reverse of the order they were made in. See
+//! [`Resolver::unresolve`](crate::Resolver::unresolve), which also checks the restored document
+//! against the current PCDB.
+
+use crate::ResolveProductsResult;
+use crate::errors::ResolvePcdbProductsError;
+use crate::report::ResolutionReport;
+use itertools::Itertools;
+use serde_json::Value as JsonValue;
+
+/// Restores a resolved document to the form it was resolved from, given the report made when it was
+/// resolved.
+///
+/// The fields that transforms inserted are removed and those they removed are put back. Fails with
+/// [`ResolvePcdbProductsError::ResolvedValuesChanged`] if any field no longer has the value recorded
+/// in the report.
+pub(crate) fn restore(
+    document: &JsonValue,
+    report: &ResolutionReport,
+) -> ResolveProductsResult<JsonValue> {
+    let mut document = document.clone();
+    let mut changed_pointers = vec![];
+
+    for module in report.modules.iter().rev() {
+        // a module's values are checked once the modules that ran after it have been undone, as they
+        // may have changed them
+        changed_pointers.extend(
+            module
+                .inserted
+                .iter()
+                .map(|field| (&field.pointer, &field.value))
+                .chain(
+                    module
+                        .changed
+                        .iter()
+                        .map(|field| (&field.pointer, &field.value)),
+                )
+                .filter(|(pointer, value)| document.pointer(pointer) != Some(value))
+                .map(|(pointer, _)| pointer.clone()),
+        );
+
+        for field in &module.changed {
+            if let Some(value) = document.pointer_mut(&field.pointer) {
+                *value = field.previous_value.clone();
+            }
+        }
+        for field in &module.inserted {
+            remove(&mut document, &field.pointer);
+        }
+        for field in &module.removed {
+            insert(&mut document, &field.pointer, field.value.clone());
+        }
+    }
+
+    if !changed_pointers.is_empty() {
+        return Err(ResolvePcdbProductsError::ResolvedValuesChanged(
+            changed_pointers,
+        ));
+    }
+
+    Ok(document)
+}
+
+fn remove(document: &mut JsonValue, pointer: &str) {
+    let Some((parent, key)) = split_pointer(pointer) else {
+        return;
+    };
+    if let Some(JsonValue::Object(parent)) = document.pointer_mut(parent) {
+        parent.remove(&key);
+    }
+}
+
+fn insert(document: &mut JsonValue, pointer: &str, value: JsonValue) {
+    let Some((parent, key)) = split_pointer(pointer) else {
+        return;
+    };
+    if let Some(JsonValue::Object(parent)) = document.pointer_mut(parent) {
+        parent.insert(key, value);
+    }
+}
+
+/// Lists the pointers of the fields that transforms inserted or changed, each once, in the order they
+/// were first written.
+pub(crate) fn resolved_pointers(report: &ResolutionReport) -> Vec<&str> {
+    report
+        .modules
+        .iter()
+        .flat_map(|module| {
+            module
+                .inserted
+                .iter()
+                .map(|field| field.pointer.as_str())
+                .chain(module.changed.iter().map(|field| field.pointer.as_str()))
+        })
+        .unique()
+        .collect()
+}
+
+/// Splits a JSON pointer into the pointer to its parent and its last (unescaped) reference token.
+fn split_pointer(pointer: &str) -> Option<(&str, String)> {
+    pointer
+        .rsplit_once('/')
+        .map(|(parent, token)| (parent, token.replace("~1", "/").replace("~0", "~")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input() -> JsonValue {
+        json!({
+            "InfiltrationVentilation": {
+                "MechanicalVentilation": {
+                    "mvhr/1": {
+                        "product_reference": "mvhr",
+                        "vent_type": "MVHR",
+                        "SFP": 1.0,
+                        "installed_under_approved_scheme": true
+                    }
+                }
+            }
+        })
+    }
+
+    fn resolved() -> JsonValue {
+        json!({
+            "InfiltrationVentilation": {
+                "MechanicalVentilation": {
+                    "mvhr/1": {"vent_type": "MVHR", "SFP": 1.5, "SFP_in_use_factor": 1.25}
+                }
+            }
+        })
+    }
+
+    fn report() -> ResolutionReport {
+        let mut report = ResolutionReport::default();
+        report.record_module_changes("mechanical_ventilation", &input(), &resolved());
+        report
+    }
+
+    #[test]
+    fn test_restore_restores_input() {
+        assert_eq!(restore(&resolved(), &report()).unwrap(), input());
+    }
+
+    #[test]
+    fn test_restore_fails_when_resolved_values_have_changed() {
+        let mut resolved = resolved();
+        resolved["InfiltrationVentilation"]["MechanicalVentilation"]["mvhr/1"]["SFP"] = json!(2.0);
+        resolved["InfiltrationVentilation"]["MechanicalVentilation"]["mvhr/1"]
+            .as_object_mut()
+            .unwrap()
+            .remove("SFP_in_use_factor");
+
+        let result = restore(&resolved, &report());
+
+        let Err(ResolvePcdbProductsError::ResolvedValuesChanged(pointers)) = result else {
+            panic!("Expected changed values to be reported, got {result:?}");
+        };
+        assert_eq!(
+            pointers,
+            [
+                "/InfiltrationVentilation/MechanicalVentilation/mvhr~11/SFP_in_use_factor",
+                "/InfiltrationVentilation/MechanicalVentilation/mvhr~11/SFP"
+            ]
+        );
+    }
+}
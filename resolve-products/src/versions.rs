@@ -0,0 +1,259 @@
+//! module provides the HEM releases that documents can be resolved for, each with its own input and
+//! target schemas and any behaviour that only applies to that release
+
+use crate::ResolveProductsResult;
+use crate::errors::{JsonValidationError, ResolvePcdbProductsError};
+use crate::warnings::{WarningCode, Warnings};
+use jsonschema::Validator;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// The HEM release whose schemas are built in to this crate, which documents are resolved for when
+/// they do not declare one.
+pub const BUILT_IN_HEM_VERSION: &str = "1.0.0-alpha7";
+
+/// JSON pointer to where a document declares the HEM release it is written for.
+const DECLARED_VERSION_POINTER: &str = "/metadata/hem_core_version";
+
+static INPUT_SCHEMA_VALIDATOR: OnceCell<Arc<Validator>> = OnceCell::const_new();
+static TARGET_SCHEMA_VALIDATOR: OnceCell<Arc<Validator>> = OnceCell::const_new();
+
+async fn input_schema_validator() -> Arc<Validator> {
+    INPUT_SCHEMA_VALIDATOR
+        .get_or_init(|| async {
+            let schema = serde_json::from_str(include_str!("./combined_schema.json"))
+                .expect("Schema file was not parseable.");
+            Arc::new(jsonschema::async_validator_for(&schema).await.expect(
+                "Failed to create validator for schema. \
+                 This is a bug in resolve-products. Please report it.",
+            ))
+        })
+        .await
+        .clone()
+}
+
+async fn target_schema_validator() -> Arc<Validator> {
+    TARGET_SCHEMA_VALIDATOR
+        .get_or_init(|| async {
+            let schema = serde_json::from_str(include_str!("./target_schema.json"))
+                .expect("Target schema file was not parseable.");
+            Arc::new(jsonschema::async_validator_for(&schema).await.expect(
+                "Failed to create validator for target schema. \
+                 This is a bug in resolve-products. Please report it.",
+            ))
+        })
+        .await
+        .clone()
+}
+
+async fn compile_schema(
+    version: &str,
+    schema: &JsonValue,
+) -> ResolveProductsResult<Arc<Validator>> {
+    jsonschema::async_validator_for(schema)
+        .await
+        .map(Arc::new)
+        .map_err(|e| {
+            ResolvePcdbProductsError::InvalidSchema(
+                version.to_owned(),
+                Box::new(JsonValidationError::from(e)),
+            )
+        })
+}
+
+/// Transform behaviour that differs between HEM releases.
+#[derive(Clone, Debug, Default)]
+pub struct VersionBehaviour {
+    /// Whether to give a wet distribution system a token `thermal_mass` of 0.001 when any of its
+    /// emitters has an `n` field and it has no `thermal_mass` already, as the 1.0.0-alpha7 schema
+    /// needs one (this is fixed in subsequent releases).
+    pub emitter_thermal_mass_placeholder: bool,
+}
+
+/// A HEM release that documents can be resolved for.
+pub struct HemVersion {
+    name: String,
+    input_schema: Arc<Validator>,
+    target_schema: Arc<Validator>,
+    behaviour: VersionBehaviour,
+}
+
+impl HemVersion {
+    /// Compiles the given input (pre-resolution) and target (post-resolution) schemas for a release.
+    pub async fn new(
+        name: impl Into<String>,
+        input_schema: &JsonValue,
+        target_schema: &JsonValue,
+        behaviour: VersionBehaviour,
+    ) -> ResolveProductsResult<Self> {
+        let name = name.into();
+        let input_schema = compile_schema(&name, input_schema).await?;
+        let target_schema = compile_schema(&name, target_schema).await?;
+
+        Ok(Self {
+            name,
+            input_schema,
+            target_schema,
+            behaviour,
+        })
+    }
+
+    async fn built_in() -> Self {
+        Self {
+            name: BUILT_IN_HEM_VERSION.into(),
+            input_schema: input_schema_validator().await,
+            target_schema: target_schema_validator().await,
+            behaviour: VersionBehaviour {
+                emitter_thermal_mass_placeholder: true,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn behaviour(&self) -> &VersionBehaviour {
+        &self.behaviour
+    }
+
+    pub(crate) fn input_schema(&self) -> &Validator {
+        &self.input_schema
+    }
+
+    pub(crate) fn target_schema(&self) -> &Validator {
+        &self.target_schema
+    }
+}
+
+/// The HEM releases that documents can be resolved for, keyed by the version they declare in
+/// `metadata.hem_core_version`.
+///
+/// Documents that do not declare a version are resolved for [`BUILT_IN_HEM_VERSION`], as are
+/// documents that declare a version that is not held here (with a warning).
+pub struct HemVersions {
+    versions: HashMap<String, HemVersion>,
+}
+
+impl HemVersions {
+    /// Creates a set of versions holding only the release built in to this crate.
+    pub async fn new() -> Self {
+        let built_in = HemVersion::built_in().await;
+
+        Self {
+            versions: HashMap::from([(built_in.name.clone(), built_in)]),
+        }
+    }
+
+    /// Adds a release, replacing any already held with the same name.
+    pub fn register(&mut self, version: HemVersion) -> &mut Self {
+        self.versions.insert(version.name.clone(), version);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&HemVersion> {
+        self.versions.get(name)
+    }
+
+    /// Selects the release that the given document should be resolved for.
+    pub(crate) fn select(&self, document: &JsonValue, warnings: &mut Warnings) -> &HemVersion {
+        let built_in = &self.versions[BUILT_IN_HEM_VERSION];
+        let Some(declared) = document
+            .pointer(DECLARED_VERSION_POINTER)
+            .and_then(JsonValue::as_str)
+        else {
+            return built_in;
+        };
+
+        self.versions.get(declared).unwrap_or_else(|| {
+            warnings.push(
+                WarningCode::UnrecognisedHemVersion,
+                DECLARED_VERSION_POINTER.into(),
+                format!(
+                    "HEM version {declared} is not one that documents can be resolved for, so the document was resolved for {BUILT_IN_HEM_VERSION}"
+                ),
+            );
+            built_in
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_built_in_versions_share_compiled_validators() {
+        let first = HemVersions::new().await;
+        let second = HemVersions::new().await;
+
+        assert!(Arc::ptr_eq(
+            &first.versions[BUILT_IN_HEM_VERSION].input_schema,
+            &second.versions[BUILT_IN_HEM_VERSION].input_schema
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_version_is_selected_from_metadata() {
+        let mut versions = HemVersions::new().await;
+        versions.register(
+            HemVersion::new(
+                "1.0.0-alpha9",
+                &json!({"type": "object"}),
+                &json!({"type": "object"}),
+                Default::default(),
+            )
+            .await
+            .unwrap(),
+        );
+        let mut warnings = Warnings::default();
+
+        let declared = versions.select(
+            &json!({"metadata": {"hem_core_version": "1.0.0-alpha9"}}),
+            &mut warnings,
+        );
+        assert_eq!(declared.name(), "1.0.0-alpha9");
+        assert!(!declared.behaviour().emitter_thermal_mass_placeholder);
+
+        let undeclared = versions.select(&json!({}), &mut warnings);
+        assert_eq!(undeclared.name(), BUILT_IN_HEM_VERSION);
+        assert!(undeclared.behaviour().emitter_thermal_mass_placeholder);
+        assert!(warnings.into_vec().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unrecognised_version_falls_back_to_built_in_version_with_warning() {
+        let versions = HemVersions::new().await;
+        let mut warnings = Warnings::default();
+
+        let selected = versions.select(
+            &json!({"metadata": {"hem_core_version": "0.1"}}),
+            &mut warnings,
+        );
+
+        assert_eq!(selected.name(), BUILT_IN_HEM_VERSION);
+        let warnings = warnings.into_vec();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, WarningCode::UnrecognisedHemVersion);
+        assert_eq!(warnings[0].pointer, "/metadata/hem_core_version");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_schema_is_rejected() {
+        let result = HemVersion::new(
+            "broken",
+            &json!({"type": 1}),
+            &json!({"type": "object"}),
+            Default::default(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ResolvePcdbProductsError::InvalidSchema(name, _)) if name == "broken"
+        ));
+    }
+}
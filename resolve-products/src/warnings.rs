@@ -0,0 +1,108 @@
+//! module provides warnings about things in a document that did not stop it being resolved, but
+//! that someone looking at the result may want to know about
+
+use serde::{Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+
+/// What a [`Warning`] is about.
+///
+/// The string form of each code (e.g. `heat_pump_test_letter_e_dropped`) is stable, so that
+/// integrators can match on it.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum WarningCode {
+    /// Heat pump test data with test letter E was left out of the resolved document, as HEM does not
+    /// accept it.
+    HeatPumpTestLetterEDropped,
+    /// A PCM heat battery product did not give a fuel, so its energy supply was assumed to be
+    /// "mains elec".
+    HeatBatteryEnergySupplyDefaulted,
+    /// A document declared a HEM version that it could not be resolved for, so it was resolved for
+    /// the version built in to this crate instead.
+    UnrecognisedHemVersion,
+    /// A warning raised by a custom [`Transformer`](crate::transformer::Transformer), with a code of
+    /// its own choosing.
+    Custom(&'static str),
+}
+
+impl WarningCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningCode::HeatPumpTestLetterEDropped => "heat_pump_test_letter_e_dropped",
+            WarningCode::HeatBatteryEnergySupplyDefaulted => "heat_battery_energy_supply_defaulted",
+            WarningCode::UnrecognisedHemVersion => "unrecognised_hem_version",
+            WarningCode::Custom(code) => code,
+        }
+    }
+}
+
+impl Serialize for WarningCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Display for WarningCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A non-fatal issue found while resolving a document.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub message: String,
+    /// JSON pointer to the part of the document the warning is about.
+    pub pointer: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} (at {})", self.code, self.message, self.pointer)
+    }
+}
+
+/// Collects the warnings raised while transforming a document.
+#[derive(Debug, Default)]
+pub(crate) struct Warnings {
+    warnings: Vec<Warning>,
+}
+
+impl Warnings {
+    pub(crate) fn push(&mut self, code: WarningCode, pointer: String, message: impl Into<String>) {
+        self.warnings.push(Warning {
+            code,
+            message: message.into(),
+            pointer,
+        });
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<Warning> {
+        self.warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_warning_serializes_with_stable_code() {
+        let mut warnings = Warnings::default();
+        warnings.push(
+            WarningCode::HeatPumpTestLetterEDropped,
+            "/HeatSourceWet/hp".into(),
+            "dropped",
+        );
+
+        assert_eq!(
+            serde_json::to_value(warnings.into_vec()).unwrap(),
+            json!([{
+                "code": "heat_pump_test_letter_e_dropped",
+                "message": "dropped",
+                "pointer": "/HeatSourceWet/hp",
+            }])
+        );
+    }
+}
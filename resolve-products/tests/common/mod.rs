@@ -12,8 +12,14 @@ use aws_sdk_dynamodb::types::{
 };
 use aws_sdk_dynamodb::{Client as DynamoDbClient, Client};
 use parking_lot::Mutex;
+use resolve_products::errors::ResolvePcdbProductsError;
+use resolve_products::in_use_factors::{
+    InUseFactorsAccess, InUseFactorsEntry, InUseFactorsInaccessibleError,
+};
+use resolve_products::{Product, ProductCatalogue, ResolveProductsResult};
 use serde_dynamo::to_item;
-use serde_json::{Value, from_str};
+use serde_json::{Value, from_str, from_value};
+use smartstring::alias::String as SmartString;
 use std::collections::HashMap;
 use std::sync::LazyLock;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -178,3 +184,97 @@ impl Drop for TestEnvironment {
         }
     }
 }
+
+/// Product catalogue and in use factors access backed by the PCDB fixture file, standing in for
+/// a catalogue implemented outside of this crate.
+pub struct InMemoryCatalogue {
+    records: HashMap<String, Value>,
+    product_lookups: AtomicUsize,
+}
+
+impl InMemoryCatalogue {
+    pub fn from_fixture() -> Self {
+        let products: HashMap<String, Value> =
+            from_str(include_str!("../fixtures/pcdb_products.json"))
+                .expect("Could not parse products file");
+
+        Self {
+            records: products
+                .into_values()
+                .map(|record| (record["id"].as_str().unwrap().to_string(), record))
+                .collect(),
+            product_lookups: Default::default(),
+        }
+    }
+
+    /// How many times products have been looked up.
+    pub fn product_lookups(&self) -> usize {
+        self.product_lookups.load(Ordering::SeqCst)
+    }
+
+    pub fn with_record(mut self, record: Value) -> Self {
+        self.records
+            .insert(record["id"].as_str().unwrap().to_string(), record);
+        self
+    }
+}
+
+impl ProductCatalogue for InMemoryCatalogue {
+    async fn find_products_for_references(
+        &self,
+        product_references: &[SmartString],
+    ) -> ResolveProductsResult<HashMap<SmartString, Product>> {
+        self.product_lookups.fetch_add(1, Ordering::SeqCst);
+        let missing_references: Vec<String> = product_references
+            .iter()
+            .filter(|reference| !self.records.contains_key(reference.as_str()))
+            .map(|reference| reference.to_string())
+            .collect();
+        if !missing_references.is_empty() {
+            return Err(ResolvePcdbProductsError::UnknownProductReferences(
+                missing_references.into(),
+            ));
+        }
+
+        Ok(product_references
+            .iter()
+            .map(|reference| {
+                let product = from_value(self.records[reference.as_str()].clone())
+                    .expect("Fixture product was expected to deserialize");
+                (reference.clone(), product)
+            })
+            .collect())
+    }
+}
+
+impl InUseFactorsAccess for InMemoryCatalogue {
+    async fn in_use_factors<T: InUseFactorsEntry>(
+        &self,
+    ) -> Result<Vec<T>, InUseFactorsInaccessibleError> {
+        let data = self
+            .records
+            .get(T::entry_id())
+            .and_then(|record| record.get("data"))
+            .ok_or(InUseFactorsInaccessibleError::DataKeyMissingFromInUseFactorsRecord)?;
+
+        Ok(from_value(data.clone()).expect("Fixture in use factors were expected to deserialize"))
+    }
+}
+
+/// Asserts that two documents are equal, comparing numbers by value so that expectations hold
+/// whether or not PCDB decimals are written exactly (where `10` and `10.0` differ).
+pub fn assert_json_eq(actual: &Value, expected: &Value) {
+    assert_eq!(numbers_as_floats(actual), numbers_as_floats(expected));
+}
+
+fn numbers_as_floats(value: &Value) -> Value {
+    match value {
+        Value::Number(number) => number.as_f64().into(),
+        Value::Array(values) => values.iter().map(numbers_as_floats).collect(),
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| (key.clone(), numbers_as_floats(value)))
+            .collect(),
+        value => value.clone(),
+    }
+}
@@ -1,18 +1,28 @@
 use itertools::Itertools;
 use jsonschema::ValidationError;
-use resolve_products::PRODUCT_REFERENCE_FIELD;
+use resolve_products::conflicts::{ConflictPolicy, FieldConflict, ResolvedEntry};
 use resolve_products::errors::ResolvePcdbProductsError;
+use resolve_products::report::ResolutionReport;
+use resolve_products::transformer::{EntryTransformer, TransformContext, TransformerRegistry};
+use resolve_products::versions::{BUILT_IN_HEM_VERSION, HemVersion, HemVersions};
+use resolve_products::warnings::WarningCode;
+use resolve_products::{
+    DynamoDbCatalogueConfig, PRODUCT_REFERENCE_FIELD, Product, ResolveProductsResult, Resolver,
+    ResolverOptions,
+};
 use rstest::rstest;
 use serde_json::{Value, from_str, json, to_string};
 use std::io::Cursor;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 mod common;
 
 static TARGET_SCHEMA: LazyLock<Value> =
-    LazyLock::new(|| from_str(include_str!("fixtures/target_schema.json")).unwrap());
+    LazyLock::new(|| from_str(include_str!("../src/target_schema.json")).unwrap());
 
 const INPUT_WITH_PRODUCT_REFS: &str = include_str!("fixtures/input_with_product_refs.json");
+const INPUT_WITH_MORE_PRODUCT_REFS: &str =
+    include_str!("fixtures/input_with_more_product_refs.json");
 
 async fn validate_against_target_schema(input: &Value) -> Result<(), ValidationError<'_>> {
     let schema_validator = jsonschema::async_validator_for(&TARGET_SCHEMA).await?;
@@ -20,11 +30,14 @@ async fn validate_against_target_schema(input: &Value) -> Result<(), ValidationE
     schema_validator.validate(input)
 }
 
-// TODO add radiator to input_with_product_refs once wet distribution thermal mass issue (reported to DESNZ) has been resolved
 #[tokio::test]
 #[rstest]
 #[case(include_str!("fixtures/demo_fhs.json"), include_str!("fixtures/demo_fhs.json"))]
 #[case(INPUT_WITH_PRODUCT_REFS, include_str!("fixtures/input_transformed.json"))]
+#[case(
+    INPUT_WITH_MORE_PRODUCT_REFS,
+    include_str!("fixtures/input_with_more_product_refs_transformed.json")
+)]
 async fn test_valid_input_succeeds(#[case] input: &str, #[case] expected_transformed: &str) {
     let environment = common::setup().await;
     let client = environment.dynamo_client();
@@ -51,7 +64,7 @@ async fn test_valid_input_succeeds(#[case] input: &str, #[case] expected_transfo
 
     assert_eq!(actual_keys, expected_keys);
     for key in expected_keys {
-        assert_eq!(transformed_input[key], expected[key], "{:?}", key);
+        common::assert_json_eq(&transformed_input[key], &expected[key]);
     }
 
     let schema_validation = validate_against_target_schema(&transformed_input).await;
@@ -63,6 +76,828 @@ async fn test_valid_input_succeeds(#[case] input: &str, #[case] expected_transfo
     );
 }
 
+#[tokio::test]
+#[rstest]
+#[case(INPUT_WITH_PRODUCT_REFS, include_str!("fixtures/input_transformed.json"))]
+#[case(
+    INPUT_WITH_MORE_PRODUCT_REFS,
+    include_str!("fixtures/input_with_more_product_refs_transformed.json")
+)]
+async fn test_valid_input_succeeds_with_custom_catalogue(
+    #[case] input: &str,
+    #[case] expected_transformed: &str,
+) {
+    let catalogue = common::InMemoryCatalogue::from_fixture();
+
+    let result = resolve_products::resolve_products_with_catalogue(
+        Cursor::new(input),
+        &catalogue,
+        &catalogue,
+    )
+    .await;
+
+    assert!(result.is_ok(), "{}", result.unwrap_err());
+
+    let transformed_input: Value = serde_json::from_reader(result.unwrap()).unwrap();
+    let expected: Value = from_str(expected_transformed).unwrap();
+
+    common::assert_json_eq(&transformed_input, &expected);
+
+    let schema_validation = validate_against_target_schema(&transformed_input).await;
+
+    assert!(
+        schema_validation.is_ok(),
+        "{:?}",
+        schema_validation.unwrap_err()
+    );
+}
+
+#[tokio::test]
+async fn test_valid_input_value_succeeds_with_custom_catalogue() {
+    let catalogue = common::InMemoryCatalogue::from_fixture();
+
+    let result = resolve_products::resolve_products_value_with_catalogue(
+        from_str(INPUT_WITH_PRODUCT_REFS).unwrap(),
+        &catalogue,
+        &catalogue,
+    )
+    .await;
+
+    let expected: Value = from_str(include_str!("fixtures/input_transformed.json")).unwrap();
+    common::assert_json_eq(&result.unwrap(), &expected);
+}
+
+#[tokio::test]
+async fn test_resolved_document_is_written_to_writers_with_custom_catalogue() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await;
+    let expected: Value = from_str(include_str!("fixtures/input_transformed.json")).unwrap();
+
+    let mut written = vec![];
+    resolver
+        .resolve_to_writer(Cursor::new(INPUT_WITH_PRODUCT_REFS), &mut written)
+        .await
+        .unwrap();
+    common::assert_json_eq(
+        &serde_json::from_slice::<Value>(&written).unwrap(),
+        &expected,
+    );
+
+    let mut written_async = vec![];
+    resolver
+        .resolve_to_async_writer(Cursor::new(INPUT_WITH_PRODUCT_REFS), &mut written_async)
+        .await
+        .unwrap();
+    assert_eq!(written_async, written);
+}
+
+#[tokio::test]
+async fn test_shared_resolver_with_custom_catalogue_resolves_across_tasks() {
+    let resolver = Arc::new(
+        Resolver::new(
+            common::InMemoryCatalogue::from_fixture(),
+            common::InMemoryCatalogue::from_fixture(),
+        )
+        .await,
+    );
+    let expected: Value = from_str(include_str!("fixtures/input_transformed.json")).unwrap();
+
+    let tasks = (0..4)
+        .map(|_| {
+            let resolver = resolver.clone();
+            tokio::spawn(async move {
+                let result = resolver.resolve(Cursor::new(INPUT_WITH_PRODUCT_REFS)).await;
+                serde_json::from_reader::<_, Value>(result.unwrap()).unwrap()
+            })
+        })
+        .collect_vec();
+
+    for task in tasks {
+        common::assert_json_eq(&task.await.unwrap(), &expected);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_with_custom_catalogue_looks_up_shared_products_once() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await;
+    let expected: Value = from_str(include_str!("fixtures/input_transformed.json")).unwrap();
+    let mut with_unknown_reference: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    with_unknown_reference["HeatSourceWet"]["Heat pump"]["product_reference"] =
+        json!("not-in-catalogue");
+    let with_unknown_reference = with_unknown_reference.to_string();
+
+    let results = resolver
+        .resolve_batch(
+            [
+                INPUT_WITH_PRODUCT_REFS,
+                "not json",
+                INPUT_WITH_PRODUCT_REFS,
+                &with_unknown_reference,
+            ]
+            .map(Cursor::new),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 4);
+    common::assert_json_eq(&results[0].as_ref().unwrap().document, &expected);
+    assert!(matches!(
+        results[1],
+        Err(ResolvePcdbProductsError::InvalidJson)
+    ));
+    common::assert_json_eq(&results[2].as_ref().unwrap().document, &expected);
+    assert!(matches!(
+        results[3],
+        Err(ResolvePcdbProductsError::UnknownProductReferences(_))
+    ));
+    // the shared lookup, once more after leaving out the unknown reference, and once more for the
+    // document with the unknown reference
+    assert_eq!(resolver.product_catalogue().product_lookups(), 3);
+}
+
+#[tokio::test]
+async fn test_report_with_custom_catalogue_gives_provenance_of_resolved_values() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(ResolverOptions {
+        report: true,
+        ..Default::default()
+    });
+
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(INPUT_WITH_PRODUCT_REFS))
+        .await
+        .unwrap();
+    let report = resolution.report.unwrap();
+
+    let heat_pump = report
+        .products
+        .iter()
+        .find(|product| product.pointer == "/HeatSourceWet/Heat pump")
+        .unwrap();
+    assert_eq!(heat_pump.product_reference, "hp");
+    assert_eq!(heat_pump.technology_type, "AirSourceHeatPump");
+    assert_eq!(heat_pump.brand_name.as_deref(), Some("Test brand name"));
+    assert_eq!(heat_pump.model_name.as_deref(), Some("test model name"));
+
+    assert_eq!(
+        report.products.len(),
+        INPUT_WITH_PRODUCT_REFS
+            .matches("\"product_reference\"")
+            .count()
+            + INPUT_WITH_PRODUCT_REFS
+                .matches("\"heat_network_reference\"")
+                .count()
+    );
+
+    let mechanical_ventilation_changes = report
+        .modules
+        .iter()
+        .find(|changes| changes.module == "mechanical_ventilation")
+        .unwrap();
+    assert!(
+        mechanical_ventilation_changes
+            .removed
+            .iter()
+            .any(|field| field.pointer
+                == "/InfiltrationVentilation/MechanicalVentilation/mech vent/product_reference")
+    );
+    assert!(report.in_use_factors.iter().any(|factor| {
+        factor.pointer
+            == "/InfiltrationVentilation/MechanicalVentilation/mech vent/SFP_in_use_factor"
+            && resolution.document.pointer(&factor.pointer) == Some(&factor.value)
+    }));
+}
+
+#[tokio::test]
+async fn test_output_validated_against_target_schema_with_custom_catalogue() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(ResolverOptions {
+        validate_output: true,
+        ..Default::default()
+    });
+
+    let result = resolver
+        .resolve_with_details(Cursor::new(INPUT_WITH_PRODUCT_REFS))
+        .await;
+
+    assert!(result.is_ok(), "{}", result.unwrap_err());
+}
+
+/// The transformer descriptions that `inspect` works from are kept by hand next to the transforms
+/// they describe, so this checks them against what resolving documents with every kind of
+/// reference actually does.
+#[tokio::test]
+async fn test_inspected_transformers_match_resolution_with_custom_catalogue() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(ResolverOptions {
+        report: true,
+        ..Default::default()
+    });
+    let documents: [Value; 2] = [
+        from_str(INPUT_WITH_PRODUCT_REFS).unwrap(),
+        from_str(INPUT_WITH_MORE_PRODUCT_REFS).unwrap(),
+    ];
+    let mut unexercised = TransformerRegistry::new().transformers();
+
+    for document in documents {
+        let inspection = resolver
+            .inspect(Cursor::new(document.to_string()))
+            .await
+            .unwrap();
+        let resolution = resolver
+            .resolve_with_details(Cursor::new(document.to_string()))
+            .await
+            .unwrap();
+        let report = resolution.report.unwrap();
+
+        for reference in inspection.references {
+            let reference_pointer = format!("{}/{}", reference.pointer, reference.field);
+            let resolved_by = report
+                .modules
+                .iter()
+                .find(|module| {
+                    // changes within arrays are reported against the whole array
+                    module
+                        .removed
+                        .iter()
+                        .any(|field| field.pointer == reference_pointer)
+                        || module.changed.iter().any(|field| {
+                            reference_pointer.starts_with(&format!("{}/", field.pointer))
+                        })
+                })
+                .map(|module| module.module.as_str());
+            assert_eq!(reference.transformer, resolved_by, "{reference_pointer}");
+
+            let entry = document
+                .pointer(&reference.pointer)
+                .and_then(Value::as_object)
+                .unwrap();
+            let technology_type = reference.product.unwrap().technology_type;
+            unexercised.retain(|info| {
+                !info.applies_to(&reference.pointer, reference.field, entry, &technology_type)
+            });
+        }
+    }
+
+    assert!(
+        unexercised.is_empty(),
+        "not exercised by any document: {unexercised:?}"
+    );
+}
+
+#[rstest]
+#[case::photovoltaic_references_swapped(
+    |mut input: Value| {
+        input["OnSiteGeneration"]["PV 1"]["product_reference"] = json!("pv_inverter");
+        input["OnSiteGeneration"]["PV 1"]["inverter_product_reference"] = json!("pv_module");
+        input
+    },
+    "/OnSiteGeneration/PV 1"
+)]
+#[case::mechanical_ventilation_of_another_vent_type(
+    |mut input: Value| {
+        input["InfiltrationVentilation"]["MechanicalVentilation"]["mech vent"]
+            ["product_reference"] = json!("mvhr");
+        input
+    },
+    "/InfiltrationVentilation/MechanicalVentilation/mech vent"
+)]
+#[tokio::test]
+async fn test_inspection_names_no_transformer_for_rejected_references_with_custom_catalogue(
+    #[case] document: fn(Value) -> Value,
+    #[case] pointer: &str,
+) {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await;
+    let document = document(from_str(INPUT_WITH_MORE_PRODUCT_REFS).unwrap());
+
+    let inspection = resolver
+        .inspect(Cursor::new(document.to_string()))
+        .await
+        .unwrap();
+    let result = resolver
+        .resolve_with_details(Cursor::new(document.to_string()))
+        .await;
+
+    assert!(result.is_err());
+    let inspected = inspection
+        .references
+        .iter()
+        .filter(|reference| reference.pointer == pointer)
+        .collect_vec();
+    assert!(!inspected.is_empty());
+    assert!(
+        inspected
+            .iter()
+            .all(|reference| reference.transformer.is_none()),
+        "{inspected:?}"
+    );
+}
+
+struct TrialHeatPump;
+
+impl EntryTransformer for TrialHeatPump {
+    fn name(&self) -> &'static str {
+        "trial_heat_pump"
+    }
+
+    fn location(&self) -> &'static str {
+        "/HeatSourceWet/*"
+    }
+
+    fn type_discriminator(&self) -> Option<&'static str> {
+        Some("HeatPump")
+    }
+
+    fn technology_types(&self) -> &'static [&'static str] {
+        &["TrialHeatPump"]
+    }
+
+    fn transform(
+        &self,
+        entry: &mut ResolvedEntry<'_>,
+        product: &Product,
+        _context: &mut TransformContext<'_>,
+    ) -> ResolveProductsResult<()> {
+        entry.insert("trial_power".into(), product.record()["trialPower"].clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_transformer_resolves_in_house_products_with_custom_catalogue() {
+    let mut transformers = TransformerRegistry::new();
+    transformers.register(TrialHeatPump);
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture().with_record(json!({
+            "id": "trial",
+            "technologyType": "TrialHeatPump",
+            "trialPower": 7.5,
+        })),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_transformers(transformers)
+    .with_options(ResolverOptions {
+        report: true,
+        ..Default::default()
+    });
+
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    input["HeatSourceWet"]["Heat pump"]["product_reference"] = json!("trial");
+
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await
+        .unwrap();
+
+    let heat_pump = &resolution.document["HeatSourceWet"]["Heat pump"];
+    assert_eq!(heat_pump["trial_power"], json!(7.5));
+    assert!(heat_pump.get(PRODUCT_REFERENCE_FIELD).is_none());
+    assert!(
+        resolution
+            .report
+            .unwrap()
+            .modules
+            .iter()
+            .any(|changes| changes.module == "trial_heat_pump")
+    );
+}
+
+struct InHouseBoiler;
+
+impl EntryTransformer for InHouseBoiler {
+    fn name(&self) -> &'static str {
+        "in_house_boiler"
+    }
+
+    fn location(&self) -> &'static str {
+        "/HeatSourceWet/*"
+    }
+
+    fn type_discriminator(&self) -> Option<&'static str> {
+        Some("Boiler")
+    }
+
+    fn technology_types(&self) -> &'static [&'static str] {
+        &["CombiBoiler"]
+    }
+
+    fn transform(
+        &self,
+        entry: &mut ResolvedEntry<'_>,
+        product: &Product,
+        _context: &mut TransformContext<'_>,
+    ) -> ResolveProductsResult<()> {
+        entry.insert(
+            "in_house_efficiency".into(),
+            product.record()["efficiencyFullLoad"].clone(),
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_transformer_takes_over_known_technology_with_custom_catalogue() {
+    let mut transformers = TransformerRegistry::new();
+    transformers.register(InHouseBoiler);
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_transformers(transformers)
+    .with_options(ResolverOptions {
+        report: true,
+        ..Default::default()
+    });
+
+    let inspection = resolver
+        .inspect(Cursor::new(INPUT_WITH_PRODUCT_REFS))
+        .await
+        .unwrap();
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(INPUT_WITH_PRODUCT_REFS))
+        .await
+        .unwrap();
+
+    let boiler = &resolution.document["HeatSourceWet"]["Regular boiler"];
+    assert_eq!(boiler["in_house_efficiency"], json!(0.8));
+    assert!(boiler.get("efficiency_full_load").is_none());
+    assert!(boiler.get(PRODUCT_REFERENCE_FIELD).is_none());
+    let boiler_reference = inspection
+        .references
+        .iter()
+        .find(|reference| reference.pointer == "/HeatSourceWet/Regular boiler")
+        .unwrap();
+    assert_eq!(boiler_reference.transformer, Some("in_house_boiler"));
+    // other heat sources are still resolved by the built-in transformer
+    assert!(
+        resolution.document["HeatSourceWet"]["Heat pump"]
+            .get(PRODUCT_REFERENCE_FIELD)
+            .is_none()
+    );
+    assert!(
+        resolution
+            .report
+            .unwrap()
+            .modules
+            .iter()
+            .any(|changes| changes.module == "in_house_boiler")
+    );
+}
+
+#[tokio::test]
+async fn test_schemas_are_selected_by_declared_hem_version_with_custom_catalogue() {
+    let mut versions = HemVersions::new().await;
+    versions.register(
+        HemVersion::new(
+            "test-version",
+            &json!({"type": "object", "required": ["TestOnlyField"]}),
+            &json!({"type": "object"}),
+            Default::default(),
+        )
+        .await
+        .unwrap(),
+    );
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_versions(versions);
+
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    input["metadata"] = json!({"hem_core_version": BUILT_IN_HEM_VERSION});
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await;
+    assert!(resolution.is_ok(), "{}", resolution.unwrap_err());
+
+    input["metadata"] = json!({"hem_core_version": "test-version"});
+    let result = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await;
+    assert!(matches!(
+        result,
+        Err(ResolvePcdbProductsError::InvalidRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_warnings_with_custom_catalogue_point_into_document() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await;
+
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(INPUT_WITH_PRODUCT_REFS))
+        .await
+        .unwrap();
+
+    let warning = resolution
+        .warnings
+        .iter()
+        .find(|warning| warning.code == WarningCode::HeatPumpTestLetterEDropped)
+        .unwrap();
+    assert_eq!(
+        warning.pointer,
+        "/HeatSourceWet/Heat pump/test_data_EN14825"
+    );
+    assert!(resolution.document.pointer(&warning.pointer).is_some());
+}
+
+#[tokio::test]
+async fn test_inspect_with_custom_catalogue_lists_references_without_resolving() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await;
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    input["HeatSourceWet"]["Heat pump"]["product_reference"] = json!("not-in-catalogue");
+
+    let inspection = resolver
+        .inspect(Cursor::new(input.to_string()))
+        .await
+        .unwrap();
+
+    assert_eq!(inspection.hem_version, BUILT_IN_HEM_VERSION);
+    let heat_pump = inspection
+        .references
+        .iter()
+        .find(|reference| reference.pointer == "/HeatSourceWet/Heat pump")
+        .unwrap();
+    assert_eq!(heat_pump.product_reference, "not-in-catalogue");
+    assert!(heat_pump.product.is_none());
+    assert!(heat_pump.transformer.is_none());
+    let hot_water_only = inspection
+        .references
+        .iter()
+        .find(|reference| reference.pointer == "/HotWaterSource/hw cylinder/HeatSource/hw only hp")
+        .unwrap();
+    assert!(hot_water_only.product.is_some());
+    assert_eq!(hot_water_only.transformer, Some("heat_pump_hw_only"));
+}
+
+#[tokio::test]
+#[rstest]
+#[case(ConflictPolicy::PcdbWins, json!("mains elec"))]
+#[case(ConflictPolicy::UserWins, json!("mains gas"))]
+async fn test_conflicting_input_values_are_recorded_with_custom_catalogue(
+    #[case] conflict_policy: ConflictPolicy,
+    #[case] expected_energy_supply: Value,
+) {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(ResolverOptions {
+        conflict_policy,
+        ..Default::default()
+    });
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    input["EnergySupply"]["mains gas"] = json!({"fuel": "mains_gas"});
+    input["HeatSourceWet"]["Regular boiler"]["EnergySupply"] = json!("mains gas");
+
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        resolution.conflicts,
+        [FieldConflict {
+            pointer: "/HeatSourceWet/Regular boiler/EnergySupply".into(),
+            input_value: json!("mains gas"),
+            pcdb_value: json!("mains elec"),
+        }]
+    );
+    assert_eq!(
+        resolution.document["HeatSourceWet"]["Regular boiler"]["EnergySupply"],
+        expected_energy_supply
+    );
+}
+
+#[tokio::test]
+async fn test_conflicting_input_values_error_when_asked_with_custom_catalogue() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(ResolverOptions {
+        conflict_policy: ConflictPolicy::ErrorOnConflict,
+        ..Default::default()
+    });
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    input["EnergySupply"]["mains gas"] = json!({"fuel": "mains_gas"});
+    input["HeatSourceWet"]["Regular boiler"]["EnergySupply"] = json!("mains gas");
+
+    let result = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ResolvePcdbProductsError::ConflictingValues(conflicts)) if conflicts.len() == 1
+    ));
+}
+
+#[tokio::test]
+#[rstest]
+#[case(ConflictPolicy::PcdbWins)]
+#[case(ConflictPolicy::UserWins)]
+async fn test_unresolve_with_custom_catalogue_restores_input(
+    #[case] conflict_policy: ConflictPolicy,
+) {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(ResolverOptions {
+        report: true,
+        conflict_policy,
+        ..Default::default()
+    });
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    input["EnergySupply"]["mains gas"] = json!({"fuel": "mains_gas"});
+    input["HeatSourceWet"]["Regular boiler"]["EnergySupply"] = json!("mains gas");
+
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await
+        .unwrap();
+    let report = resolution.report.unwrap();
+
+    assert_eq!(
+        resolver
+            .unresolve(&resolution.document, &report)
+            .await
+            .unwrap(),
+        input
+    );
+
+    let mut edited = resolution.document.clone();
+    edited["HeatSourceWet"]["Heat pump"]["power_standby"] = json!(0.05);
+    assert!(matches!(
+        resolver.unresolve(&edited, &report).await,
+        Err(ResolvePcdbProductsError::ResolvedValuesChanged(pointers))
+            if pointers == ["/HeatSourceWet/Heat pump/power_standby"]
+    ));
+}
+
+#[tokio::test]
+async fn test_unresolve_with_custom_catalogue_checks_saved_report_against_current_pcdb() {
+    let options = ResolverOptions {
+        report: true,
+        ..Default::default()
+    };
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(options.clone());
+    let input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+
+    let resolution = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await
+        .unwrap();
+    let document: Value = from_str(&to_string(&resolution.document).unwrap()).unwrap();
+    let report: ResolutionReport =
+        from_str(&to_string(&resolution.report.unwrap()).unwrap()).unwrap();
+
+    assert_eq!(resolver.unresolve(&document, &report).await.unwrap(), input);
+
+    let mut updated_product =
+        from_str::<Value>(include_str!("fixtures/pcdb_products.json")).unwrap()["hp"].clone();
+    updated_product["powerStandby"] = json!(0.03);
+    let updated_resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture().with_record(updated_product),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(options);
+    assert!(matches!(
+        updated_resolver.unresolve(&document, &report).await,
+        Err(ResolvePcdbProductsError::ResolvedValuesChanged(pointers))
+            if pointers == ["/HeatSourceWet/Heat pump/power_standby"]
+    ));
+}
+
+#[tokio::test]
+async fn test_all_errors_are_collected_with_custom_catalogue() {
+    let resolver = Resolver::new(
+        common::InMemoryCatalogue::from_fixture(),
+        common::InMemoryCatalogue::from_fixture(),
+    )
+    .await
+    .with_options(ResolverOptions {
+        collect_all_errors: true,
+        ..Default::default()
+    });
+
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    input["HotWaterSource"]["hw cylinder"]["HeatSource"]["hw only hp"]["product_reference"] =
+        json!("smart_tank");
+    input["HeatSourceWet"]["Heat pump"]["product_reference"] = json!("boiler");
+
+    let result = resolver
+        .resolve_with_details(Cursor::new(input.to_string()))
+        .await;
+
+    let Err(ResolvePcdbProductsError::ResolutionErrors(errors)) = result else {
+        panic!("Expected all resolution errors to be returned, got {result:?}");
+    };
+    assert_eq!(
+        errors
+            .iter()
+            .map(|error| error.pointer.as_str())
+            .sorted()
+            .collect_vec(),
+        [
+            "/HeatSourceWet/Heat pump",
+            "/HotWaterSource/hw cylinder/HeatSource/hw only hp"
+        ]
+    );
+    assert!(errors.iter().all(|error| matches!(
+        error.error,
+        ResolvePcdbProductsError::ProductCategoryMismatches(_)
+    )));
+}
+
+#[tokio::test]
+async fn test_references_at_unsupported_locations_error_with_custom_catalogue() {
+    let catalogue = common::InMemoryCatalogue::from_fixture();
+    let mut input: Value = from_str(INPUT_WITH_PRODUCT_REFS).unwrap();
+    // combi boilers are not yet resolved from the PCDB, so this reference is left unconsumed
+    input["HotWaterSource"]["hw cylinder"] = json!({
+        "type": "CombiBoiler",
+        "product_reference": "boiler",
+        "ColdWaterSource": "mains water",
+        "HeatSourceWet": "Heat pump",
+    });
+
+    let result = resolve_products::resolve_products_with_catalogue(
+        Cursor::new(input.to_string()),
+        &catalogue,
+        &catalogue,
+    )
+    .await;
+
+    let Err(ResolvePcdbProductsError::UnconsumedProductReferences(pointers)) = result else {
+        panic!("Expected unconsumed product references to be reported, got {result:?}");
+    };
+    assert_eq!(pointers, ["/HotWaterSource/hw cylinder/product_reference"]);
+}
+
+#[tokio::test]
+async fn test_unknown_product_ref_errors_with_custom_catalogue() {
+    let catalogue = common::InMemoryCatalogue::from_fixture();
+
+    let result = resolve_products::resolve_products_with_catalogue(
+        Cursor::new(include_str!(
+            "fixtures/input_with_unknown_product_refs.json"
+        )),
+        &catalogue,
+        &catalogue,
+    )
+    .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ResolvePcdbProductsError::UnknownProductReferences(_)
+    ));
+}
+
 #[tokio::test]
 async fn test_unknown_product_ref_errors() {
     let environment = common::setup().await;
@@ -81,6 +916,51 @@ async fn test_unknown_product_ref_errors() {
     ));
 }
 
+#[tokio::test]
+async fn test_configured_table_name_is_used() {
+    let environment = common::setup().await;
+    let client = environment.dynamo_client();
+
+    let mut input_reader = Cursor::new(INPUT_WITH_PRODUCT_REFS);
+
+    let result = resolve_products::resolve_products_with_dynamo_db_config(
+        &mut input_reader,
+        client,
+        DynamoDbCatalogueConfig {
+            table_name: "products_not_here".into(),
+            consistent_read: true,
+            ..Default::default()
+        },
+    )
+    .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ResolvePcdbProductsError::AccessError(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_configured_table_name_is_used_for_values() {
+    let environment = common::setup().await;
+    let client = environment.dynamo_client();
+
+    let result = resolve_products::resolve_products_value_with_dynamo_db_config(
+        from_str(INPUT_WITH_PRODUCT_REFS).unwrap(),
+        client,
+        DynamoDbCatalogueConfig {
+            table_name: "products_not_here".into(),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ResolvePcdbProductsError::AccessError(_)
+    ));
+}
+
 #[tokio::test]
 async fn test_invalid_json_errors() {
     let environment = common::setup().await;